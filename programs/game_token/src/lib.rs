@@ -1,147 +1,326 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{Mint, TokenInterface, TokenAccount, TransferChecked, MintTo};
-use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenInterface, TokenAccount, TransferChecked, MintTo, Burn};
+use anchor_spl::token_2022::{spl_token_2022, Token2022};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022_extensions::transfer_fee::{
+    HarvestWithheldTokensToMint, WithdrawWithheldTokensFromMint,
+};
+use anchor_spl::token_2022_extensions::{
+    spl_token_metadata_interface::state::TokenMetadata, MetadataPointerInitialize,
+    TokenMetadataInitialize,
+};
+use spl_token_2022::extension::{
+    transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use solana_instructions_sysvar as instructions_sysvar;
+use solana_sdk_ids::{ed25519_program, sysvar::instructions::ID as INSTRUCTIONS_SYSVAR_ID};
 
 declare_id!("Do9Bq3c7rSSU4YW32F3mCZekQZo5jdyaBuayqmNGAeTf");
 
+// Maximum number of minter operator keys the owner can register
+pub const MAX_MINTERS: usize = 10;
+
+// Pause flags stored in MintingAuthority.pause_flags
+pub const PAUSE_MINT: u8 = 1 << 0;
+pub const PAUSE_EARN: u8 = 1 << 1;
+pub const PAUSE_CLAIM: u8 = 1 << 2;
+pub const PAUSE_GLOBAL: u8 = 1 << 7;
+pub const PAUSE_FLAGS_MASK: u8 = PAUSE_MINT | PAUSE_EARN | PAUSE_CLAIM | PAUSE_GLOBAL;
+
+// Queued parameter changes that are not executed within this window after
+// their ETA become stale and must be queued again
+pub const TIMELOCK_GRACE_PERIOD: i64 = 14 * 86_400;
+
+// Earn voucher message: program id + player + amount + nonce + expiry
+pub const EARN_VOUCHER_MESSAGE_LEN: usize = 32 + 32 + 8 + 8 + 8;
+
+// Achievement attestation message: program id + player + achievement id
+pub const ACHIEVEMENT_VOUCHER_MESSAGE_LEN: usize = 32 + 32 + 2;
+
+// Achievement ids index a per-player bitmap of MAX_ACHIEVEMENTS bits
+pub const MAX_ACHIEVEMENTS: usize = 256;
+
+// Players kept on each season leaderboard, and prize table length
+pub const LEADERBOARD_SIZE: usize = 10;
+
+// Daily check-in: days are UTC days since the epoch; streaks past the end of the
+// reward table keep earning its last entry
+pub const SECONDS_PER_DAY: i64 = 86_400;
+pub const MAX_STREAK_TABLE: usize = 30;
+
+// Multisig limits: owner set size and the size of a proposed instruction
+pub const MAX_MULTISIG_OWNERS: usize = 10;
+pub const MAX_MULTISIG_TX_ACCOUNTS: usize = 16;
+pub const MAX_MULTISIG_TX_DATA: usize = 512;
+
+// Mint split: recipients and basis-point weights summing to BPS_DENOMINATOR
+pub const MAX_SPLIT_RECIPIENTS: usize = 8;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+// Fixed-point scale for the emission decay factor
+pub const EMISSION_DECAY_SCALE: u128 = 1_000_000_000_000_000_000;
+
+// Maximum emission epochs settled by one crank_emission call; later calls catch up
+pub const MAX_CRANK_EPOCHS: usize = 32;
+
+// Staking: reward-per-token is scaled by REWARD_PRECISION, APR accrues per second
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+pub const SECONDS_PER_YEAR: u128 = 365 * 86_400;
+// Pending unstake requests per player, each with its own cooldown
+pub const MAX_UNSTAKE_REQUESTS: usize = 8;
+
+// Owner vesting: deposits are grouped into at most MAX_VESTING_TRANCHES live tranches,
+// one per tranche interval, see VestingEscrow
+pub const MAX_VESTING_TRANCHES: usize = 32;
+
+// Token-2022 metadata limits for the game mint
+pub const MAX_TOKEN_NAME_LEN: usize = 32;
+pub const MAX_TOKEN_SYMBOL_LEN: usize = 10;
+pub const MAX_TOKEN_URI_LEN: usize = 200;
+
 #[program]
 pub mod game_token {
     use super::*;
 
-    // Initialize game pools and token account
-    pub fn initialize_game_pools(
-        ctx: Context<InitializeGamePools>,
-        bump: u8
+    // Set up a fresh deployment in one instruction: the MintingAuthority, the game
+    // mint at the game_token_mint PDA (see create_game_mint), the game pools and the
+    // game pools token account. The signer becomes owner and pools authority
+    pub fn initialize_game(
+        ctx: Context<InitializeGame>,
+        max_mints_per_player_per_minute: u8,
+        is_infinite: bool,
+        max_supply: u64,
+        decimals: u8,
+        freeze_authority: Option<Pubkey>,
+        metadata: Option<GameMintMetadata>,
     ) -> Result<()> {
-        let game_pools = &mut ctx.accounts.game_pools;
+        ctx.accounts.authority.initialize(
+            ctx.accounts.owner.key(),
+            max_mints_per_player_per_minute,
+            is_infinite,
+            max_supply,
+            ctx.bumps.authority,
+        );
 
-        game_pools.authority = ctx.accounts.authority.key();
-        game_pools.active_pool = 0;
-        game_pools.reward_pool = 0;
-        game_pools.reserve_pool = 0;
-        game_pools.burn_pool = 0;
-        game_pools.game_token_mint = ctx.accounts.game_token_mint.key();
-        game_pools.bump = bump;
+        let has_metadata = metadata.is_some();
+        create_game_mint(
+            GameMintAccounts {
+                authority: ctx.accounts.authority.to_account_info(),
+                authority_bump: ctx.bumps.authority,
+                game_token_mint: ctx.accounts.game_token_mint.to_account_info(),
+                mint_bump: ctx.bumps.game_token_mint,
+                owner: ctx.accounts.owner.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            decimals,
+            freeze_authority,
+            metadata,
+        )?;
 
-        msg!("Initialized GamePools PDA: {:?}", game_pools.key());
+        // Token account for the mint just created, so it cannot be an `init` constraint
+        let mint_extensions = if has_metadata {
+            vec![ExtensionType::MetadataPointer]
+        } else {
+            vec![]
+        };
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Account>(
+            &ExtensionType::get_required_init_account_extensions(&mint_extensions),
+        )?;
+        create_pda_account(
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.game_pools_token_account.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            space,
+            &ctx.accounts.token_program.key(),
+            &[b"game_pools_token_account", &[ctx.bumps.game_pools_token_account]],
+        )?;
+        anchor_spl::token_2022::initialize_account3(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token_2022::InitializeAccount3 {
+                account: ctx.accounts.game_pools_token_account.to_account_info(),
+                mint: ctx.accounts.game_token_mint.to_account_info(),
+                authority: ctx.accounts.game_pools.to_account_info(),
+            },
+        ))?;
+
+        let game_pools_key = ctx.accounts.game_pools.key();
+        ctx.accounts.game_pools.initialize(
+            ctx.accounts.owner.key(),
+            ctx.accounts.game_token_mint.key(),
+            ctx.bumps.game_pools,
+        );
+
+        msg!(
+            "Initialized game: mint {:?}, GamePools PDA {:?}",
+            ctx.accounts.game_token_mint.key(),
+            game_pools_key
+        );
         Ok(())
     }
 
-    // Initialize minting authority
-    pub fn initialize_minting_authority(
-        ctx: Context<InitializeMintingAuthority>,
-        max_mints_per_player_per_minute: u8,
-        is_infinite: bool,
-        max_supply: u64,
+    // Create the game mint for an existing MintingAuthority (owner only), see create_game_mint
+    pub fn initialize_game_mint(
+        ctx: Context<InitializeGameMint>,
+        decimals: u8,
+        freeze_authority: Option<Pubkey>,
+        metadata: Option<GameMintMetadata>,
     ) -> Result<()> {
-        let authority = &mut ctx.accounts.authority;
-        let owner = &ctx.accounts.owner;
+        create_game_mint(
+            GameMintAccounts {
+                authority: ctx.accounts.authority.to_account_info(),
+                authority_bump: ctx.accounts.authority.bump,
+                game_token_mint: ctx.accounts.game_token_mint.to_account_info(),
+                mint_bump: ctx.bumps.game_token_mint,
+                owner: ctx.accounts.owner.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            decimals,
+            freeze_authority,
+            metadata,
+        )
+    }
 
-        // Set initial values
-        authority.owner = owner.key();
-        authority.total_minted = 0;
-        authority.is_infinite = is_infinite;
-        authority.max_supply = max_supply;
-        authority.max_mints_per_player_per_minute = max_mints_per_player_per_minute;
-        authority.bump = ctx.bumps.authority;
+    // Initialize game pools and token account for the game_token_mint PDA, for
+    // deployments whose MintingAuthority and mint already exist. A fresh deployment
+    // uses initialize_game instead
+    pub fn initialize_game_pools(
+        ctx: Context<InitializeGamePools>,
+        bump: u8
+    ) -> Result<()> {
+        let game_pools = &mut ctx.accounts.game_pools;
+        game_pools.initialize(ctx.accounts.authority.key(), ctx.accounts.game_token_mint.key(), bump);
 
-        msg!("Initialized MintingAuthority for owner: {:?}", owner.key());
+        msg!("Initialized GamePools PDA: {:?}", game_pools.key());
         Ok(())
     }
 
-    // DISABLED: Old logic - Player eat particle = Mint token (WRONG APPROACH)
-    // This function is DISABLED because it requires player activity for owner revenue
-    // Use auto_mint_tokens() instead for scheduled, independent minting
+    // Create the reward, reserve and burn pool token accounts (pools authority only)
+    pub fn initialize_pool_token_accounts(ctx: Context<InitializePoolTokenAccounts>) -> Result<()> {
+        let game_pools = &mut ctx.accounts.game_pools;
+        game_pools.reward_pool_token_account = ctx.accounts.reward_pool_token_account.key();
+        game_pools.reserve_pool_token_account = ctx.accounts.reserve_pool_token_account.key();
+        game_pools.burn_pool_token_account = ctx.accounts.burn_pool_token_account.key();
 
-    // pub fn eat_energy_particle(...) -> DISABLED
+        msg!(
+            "Initialized pool token accounts: reward {:?}, reserve {:?}, burn {:?}",
+            game_pools.reward_pool_token_account,
+            game_pools.reserve_pool_token_account,
+            game_pools.burn_pool_token_account
+        );
+        Ok(())
+    }
 
-    // Auto-mint tokens theo schedule (Logic Mới - Independent of players)
-    pub fn auto_mint_tokens(
-        ctx: Context<AutoMintTokens>,
-        amount: u64
+    // Limits for transfer_between_pools (pools authority only), 0 disables a limit
+    pub fn set_pool_transfer_limits(
+        ctx: Context<ManageGamePools>,
+        max_pool_transfer_amount: u64,
+        max_pool_transfer_per_day: u64,
     ) -> Result<()> {
-        let authority = &mut ctx.accounts.authority;
         let game_pools = &mut ctx.accounts.game_pools;
-        let current_time = Clock::get()?.unix_timestamp;
+        game_pools.max_pool_transfer_amount = max_pool_transfer_amount;
+        game_pools.max_pool_transfer_per_day = max_pool_transfer_per_day;
 
-        // Check supply limits if not infinite
-        if !authority.is_infinite {
-            require!(
-                authority.total_minted + amount <= authority.max_supply,
-                GameTokenError::SupplyLimitExceeded
-            );
-        }
+        emit!(PoolTransferLimitsUpdatedEvent {
+            max_pool_transfer_amount,
+            max_pool_transfer_per_day,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-        // Calculate distribution (80/20 split)
-        let game_amount = amount * 80 / 100;  // 80% cho game pool
-        let owner_amount = amount * 20 / 100; // 20% cho owner
+        msg!(
+            "Pool transfer limits set: {} per transfer, {} per day",
+            max_pool_transfer_amount,
+            max_pool_transfer_per_day
+        );
+        Ok(())
+    }
 
-        // Mint tokens to game pools token account (80%)
-        anchor_spl::token_interface::mint_to(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    mint: ctx.accounts.game_token_mint.to_account_info(),
-                    to: ctx.accounts.game_pools_token_account.to_account_info(),
-                    authority: authority.to_account_info(),
-                },
-            ),
-            game_amount,
-        )?;
+    // Move tokens between two pools (pools authority only), within the transfer limits
+    pub fn transfer_between_pools(
+        ctx: Context<TransferBetweenPools>,
+        from: PoolKind,
+        to: PoolKind,
+        amount: u64,
+    ) -> Result<()> {
+        // Admin pool movements only stop under a global pause
+        require!(
+            !ctx.accounts.minting_authority.is_paused(PAUSE_GLOBAL),
+            GameTokenError::OperationPaused
+        );
+        require!(from != to && amount > 0, GameTokenError::InvalidPoolTransfer);
 
-        // Mint owner tokens (20%)
-        anchor_spl::token_interface::mint_to(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                MintTo {
-                    mint: ctx.accounts.game_token_mint.to_account_info(),
-                    to: ctx.accounts.owner_token_account.to_account_info(),
-                    authority: authority.to_account_info(),
+        let game_pools_bump = ctx.accounts.game_pools.bump;
+        let current_time = Clock::get()?.unix_timestamp;
+        let fee = transfer_fee_for(&ctx.accounts.game_token_mint, amount)?;
+        ctx.accounts
+            .game_pools
+            .record_pool_transfer(amount, current_time)?;
+
+        let game_pools = &mut ctx.accounts.game_pools;
+        let from_balance = game_pools.pool_balance_mut(from);
+        *from_balance = from_balance
+            .checked_sub(amount)
+            .ok_or(GameTokenError::InsufficientPool)?;
+        // The destination only receives the amount net of any transfer fee
+        let to_balance = game_pools.pool_balance_mut(to);
+        *to_balance = to_balance
+            .checked_add(amount - fee)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        let accounts = &ctx.accounts;
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: accounts.pool_token_account(from),
+                    to: accounts.pool_token_account(to),
+                    authority: accounts.game_pools.to_account_info(),
+                    mint: accounts.game_token_mint.to_account_info(),
                 },
+                &[&[
+                    b"game_pools",
+                    &[game_pools_bump]
+                ]]
             ),
-            owner_amount,
+            amount,
+            accounts.game_token_mint.decimals,
         )?;
 
-        // Update tracking
-        authority.total_minted += amount;
-        game_pools.active_pool += game_amount;
-
-        // Emit event
-        emit!(AutoMintEvent {
+        emit!(PoolTransferEvent {
+            from,
+            to,
             amount,
-            game_amount,
-            owner_amount,
+            fee,
+            from_balance: accounts.game_pools.pool_balance(from),
+            to_balance: accounts.game_pools.pool_balance(to),
             timestamp: current_time,
         });
 
-        msg!("Auto-minted {} tokens: {} game + {} owner", amount, game_amount, owner_amount);
+        msg!("Moved {} tokens from {:?} pool to {:?} pool", amount, from, to);
         Ok(())
     }
 
-    // CORE CONCEPT: Player earns from pre-minted game pool (CORRECT APPROACH)
-    // Game pool is filled by auto_mint_tokens() scheduler - independent of players
-    // Players only earn from existing pool balance
-    pub fn player_earn_from_pool(
-        ctx: Context<PlayerEarnFromPool>,
-        amount: u64
+    // Burn tokens held by the burn pool (pools authority only)
+    pub fn burn_from_burn_pool(
+        ctx: Context<BurnFromBurnPool>,
+        amount: u64,
     ) -> Result<()> {
+        require!(
+            !ctx.accounts.minting_authority.is_paused(PAUSE_GLOBAL),
+            GameTokenError::OperationPaused
+        );
         let game_pools_bump = ctx.accounts.game_pools.bump;
-        let current_time = Clock::get()?.unix_timestamp;
-        let current_minute = current_time / 60;
+        require!(ctx.accounts.game_pools.burn_pool >= amount, GameTokenError::InsufficientPool);
 
-        // Check if pool has enough tokens (pool filled by auto-mint scheduler)
-        require!(ctx.accounts.game_pools.active_pool >= amount, GameTokenError::InsufficientPool);
-
-        // Transfer từ game pool cho player (80% từ auto-mint)
-        // Sử dụng game_pools_token_account làm nguồn và game_pools PDA làm authority
-        anchor_spl::token_interface::transfer_checked(
+        anchor_spl::token_interface::burn(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.game_pools_token_account.to_account_info(),
-                    to: ctx.accounts.player_token_account.to_account_info(),
-                    authority: ctx.accounts.game_pools.to_account_info(),
+                Burn {
                     mint: ctx.accounts.game_token_mint.to_account_info(),
+                    from: ctx.accounts.burn_pool_token_account.to_account_info(),
+                    authority: ctx.accounts.game_pools.to_account_info(),
                 },
                 &[&[
                     b"game_pools",
@@ -149,59 +328,110 @@ pub mod game_token {
                 ]]
             ),
             amount,
-            6, // decimals
         )?;
 
-        // Update tracking
         let game_pools = &mut ctx.accounts.game_pools;
-        let player_stats = &mut ctx.accounts.player_stats;
-        game_pools.active_pool -= amount;
-        player_stats.session_tokens += amount;
-        player_stats.total_earned += amount;
+        game_pools.burn_pool -= amount;
 
-        // Rate limiting cho player earn
-        if current_minute > player_stats.last_mint_minute {
-            player_stats.last_mint_minute = current_minute;
-            player_stats.mints_this_minute = 0;
+        emit!(PoolBurnEvent {
+            amount,
+            remaining_burn_pool: game_pools.burn_pool,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Burned {} tokens from burn pool", amount);
+        Ok(())
+    }
+
+    // Set the global earn budget shared by all players per epoch (pools authority only)
+    // A budget of 0 disables the limit
+    pub fn set_earn_budget(
+        ctx: Context<ManageGamePools>,
+        earn_budget_per_epoch: u64,
+        earn_epoch_length: i64,
+    ) -> Result<()> {
+        require!(earn_epoch_length > 0, GameTokenError::InvalidEarnBudget);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let game_pools = &mut ctx.accounts.game_pools;
+        // Changing the epoch length restarts the budget window
+        if game_pools.earn_epoch_length != earn_epoch_length {
+            game_pools.earn_epoch = current_time / earn_epoch_length;
+            game_pools.earned_this_epoch = 0;
         }
-        player_stats.mints_this_minute += 1;
+        game_pools.earn_budget_per_epoch = earn_budget_per_epoch;
+        game_pools.earn_epoch_length = earn_epoch_length;
 
-        emit!(PlayerEarnedEvent {
-            player: ctx.accounts.player.key(),
-            amount,
-            remaining_pool: game_pools.active_pool,
+        emit!(EarnBudgetUpdatedEvent {
+            earn_budget_per_epoch,
+            earn_epoch_length,
+            remaining_earn_budget: game_pools.remaining_earn_budget(current_time),
             timestamp: current_time,
         });
 
-        msg!("Player {} earned {} tokens from auto-filled game pool", ctx.accounts.player.key(), amount);
+        msg!("Earn budget set to {} per {}s epoch", earn_budget_per_epoch, earn_epoch_length);
         Ok(())
     }
 
-    // DISABLED: Legacy earn_tokens function (WRONG APPROACH)
-    // This function is DISABLED - use player_earn_from_pool instead
-    // player_earn_from_pool has proper player tracking and rate limiting
+    // Configure automatic reserve -> active top-ups (pools authority only)
+    // A low watermark of 0 disables rebalancing
+    pub fn set_rebalance_config(
+        ctx: Context<ManageGamePools>,
+        active_low_watermark: u64,
+        active_high_watermark: u64,
+        max_rebalance_per_call: u64,
+    ) -> Result<()> {
+        require!(
+            active_low_watermark <= active_high_watermark && max_rebalance_per_call > 0,
+            GameTokenError::InvalidRebalanceConfig
+        );
 
-    // pub fn earn_tokens(...) -> DISABLED
+        let game_pools = &mut ctx.accounts.game_pools;
+        game_pools.active_low_watermark = active_low_watermark;
+        game_pools.active_high_watermark = active_high_watermark;
+        game_pools.max_rebalance_per_call = max_rebalance_per_call;
+
+        emit!(RebalanceConfigUpdatedEvent {
+            active_low_watermark,
+            active_high_watermark,
+            max_rebalance_per_call,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Rebalance config set: low {}, high {}, max {} per call",
+            active_low_watermark,
+            active_high_watermark,
+            max_rebalance_per_call
+        );
+        Ok(())
+    }
+
+    // Refill the active pool from the reserve pool once it drops under the low
+    // watermark, up to the high watermark and capped per call (permissionless)
+    pub fn rebalance_pools(ctx: Context<RebalancePools>) -> Result<()> {
+        // Top-ups only serve earning, so they stop with it
+        require!(!ctx.accounts.authority.is_paused(PAUSE_EARN), GameTokenError::OperationPaused);
 
-    // Player claim tokens from treasury (User-specified amount)
-    pub fn player_claim_tokens(
-        ctx: Context<PlayerClaimTokens>,
-        amount: u64
-    ) -> Result<()> {
         let game_pools_bump = ctx.accounts.game_pools.bump;
-        let current_time = Clock::get()?.unix_timestamp;
+        let game_pools = &ctx.accounts.game_pools;
+        require!(
+            game_pools.active_pool < game_pools.active_low_watermark,
+            GameTokenError::RebalanceNotNeeded
+        );
 
-        // Check if treasury has enough tokens
-        require!(ctx.accounts.game_pools.active_pool >= amount, GameTokenError::InsufficientPool);
+        let amount = (game_pools.active_high_watermark - game_pools.active_pool)
+            .min(game_pools.max_rebalance_per_call)
+            .min(game_pools.reserve_pool);
+        require!(amount > 0, GameTokenError::InsufficientPool);
+        let fee = transfer_fee_for(&ctx.accounts.game_token_mint, amount)?;
 
-        // Transfer từ treasury (game_pools_token_account) cho player
-        // Player tự trả phí transaction
         anchor_spl::token_interface::transfer_checked(
             CpiContext::new_with_signer(
                 ctx.accounts.token_program.to_account_info(),
                 TransferChecked {
-                    from: ctx.accounts.game_pools_token_account.to_account_info(),
-                    to: ctx.accounts.player_token_account.to_account_info(),
+                    from: ctx.accounts.reserve_pool_token_account.to_account_info(),
+                    to: ctx.accounts.game_pools_token_account.to_account_info(),
                     authority: ctx.accounts.game_pools.to_account_info(),
                     mint: ctx.accounts.game_token_mint.to_account_info(),
                 },
@@ -211,203 +441,6278 @@ pub mod game_token {
                 ]]
             ),
             amount,
-            6, // decimals
+            ctx.accounts.game_token_mint.decimals,
         )?;
 
-        // Update tracking
         let game_pools = &mut ctx.accounts.game_pools;
-        let player_stats = &mut ctx.accounts.player_stats;
-        game_pools.active_pool -= amount;
-        player_stats.total_claimed += amount;
+        game_pools.reserve_pool -= amount;
+        game_pools.active_pool += amount - fee;
 
-        // Emit claim event
-        emit!(PlayerClaimedEvent {
-            player: ctx.accounts.player.key(),
+        emit!(PoolsRebalancedEvent {
+            caller: ctx.accounts.caller.key(),
             amount,
-            remaining_pool: game_pools.active_pool,
-            timestamp: current_time,
+            fee,
+            active_pool: game_pools.active_pool,
+            reserve_pool: game_pools.reserve_pool,
+            timestamp: Clock::get()?.unix_timestamp,
         });
 
-        msg!("Player {} claimed {} tokens from treasury", ctx.accounts.player.key(), amount);
+        msg!("Rebalanced {} tokens from reserve to active pool", amount);
         Ok(())
     }
 
-    // Emergency pause (owner only)
-    pub fn emergency_pause(ctx: Context<EmergencyControl>) -> Result<()> {
-        let authority = &mut ctx.accounts.authority;
-        authority.is_infinite = false; // Effectively pause by setting finite supply
-        authority.max_supply = authority.total_minted; // No more minting
+    // Register the game-server key that signs earn vouchers (pools authority only)
+    pub fn set_game_server(
+        ctx: Context<ManageGamePools>,
+        game_server: Pubkey,
+    ) -> Result<()> {
+        let game_pools = &mut ctx.accounts.game_pools;
+        let previous = game_pools.game_server;
+        game_pools.game_server = game_server;
 
-        emit!(EmergencyPausedEvent {
-            owner: ctx.accounts.owner.key(),
+        emit!(GameServerUpdatedEvent {
+            previous,
+            game_server,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
-        msg!("Emergency pause activated by owner");
+        msg!("Game server key set to {:?}", game_server);
         Ok(())
     }
-}
-
-// Account Structures
-#[account]
-#[derive(Default, InitSpace)]
-pub struct MintingAuthority {
-    pub owner: Pubkey,
-    pub total_minted: u64,
-    pub is_infinite: bool,
-    pub max_supply: u64,
-    pub max_mints_per_player_per_minute: u8,
-    pub bump: u8,
-}
 
-#[account]
-#[derive(Default, InitSpace)]
-pub struct PlayerMintStats {
-    pub player: Pubkey,
-    pub session_tokens: u64,
-    pub last_mint_minute: i64,
-    pub mints_this_minute: u8,
-    pub total_earned: u64,
-    pub total_claimed: u64,
-    pub bump: u8,
-}
+    // Propose a new GamePools authority, who must accept with their own signature
+    pub fn propose_pools_owner(
+        ctx: Context<ManageGamePools>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        let game_pools = &mut ctx.accounts.game_pools;
+        require!(
+            new_owner != Pubkey::default() && new_owner != game_pools.authority,
+            GameTokenError::InvalidNewOwner
+        );
+        game_pools.pending_authority = new_owner;
 
-#[account]
-#[derive(Default, InitSpace)]
-pub struct GameTokenPools {
-    pub authority: Pubkey,
-    pub active_pool: u64,
-    pub reward_pool: u64,
-    pub reserve_pool: u64,
-    pub burn_pool: u64,
-    pub game_token_mint: Pubkey,
-    pub bump: u8,
-}
+        emit!(OwnerTransferProposedEvent {
+            account: game_pools.key(),
+            current_owner: game_pools.authority,
+            pending_owner: new_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-// Events
-#[event]
-pub struct AutoMintEvent {
-    pub amount: u64,
-    pub game_amount: u64,
-    pub owner_amount: u64,
-    pub timestamp: i64,
-}
+        msg!("Proposed GamePools authority transfer to {:?}", new_owner);
+        Ok(())
+    }
 
-#[event]
-pub struct PlayerEarnedEvent {
-    pub player: Pubkey,
-    pub amount: u64,
-    pub remaining_pool: u64,
-    pub timestamp: i64,
-}
+    // Pending GamePools authority accepts the transfer
+    pub fn accept_pools_owner(ctx: Context<AcceptPoolsOwner>) -> Result<()> {
+        let game_pools = &mut ctx.accounts.game_pools;
+        let previous_owner = game_pools.authority;
+        game_pools.authority = ctx.accounts.new_owner.key();
+        game_pools.pending_authority = Pubkey::default();
 
-#[event]
-pub struct PlayerClaimedEvent {
-    pub player: Pubkey,
-    pub amount: u64,
-    pub remaining_pool: u64,
-    pub timestamp: i64,
-}
+        emit!(OwnerTransferAcceptedEvent {
+            account: game_pools.key(),
+            previous_owner,
+            new_owner: game_pools.authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-// DISABLED: Old events - WRONG APPROACH
-// TokenMintedEvent is DISABLED because eat_energy_particle is disabled
-// TokensEarnedEvent is DISABLED because earn_tokens is disabled
-// Use AutoMintEvent and PlayerEarnedEvent instead
+        msg!("GamePools authority transferred to {:?}", game_pools.authority);
+        Ok(())
+    }
 
-// #[event]
-// pub struct TokenMintedEvent { ... } DISABLED
+    // Current GamePools authority withdraws a pending transfer
+    pub fn cancel_pools_owner_transfer(ctx: Context<ManageGamePools>) -> Result<()> {
+        let game_pools = &mut ctx.accounts.game_pools;
+        let cancelled_owner = game_pools.pending_authority;
+        require!(cancelled_owner != Pubkey::default(), GameTokenError::NoPendingOwner);
+        game_pools.pending_authority = Pubkey::default();
 
-// #[event]
-// pub struct TokensEarnedEvent { ... } DISABLED
+        emit!(OwnerTransferCancelledEvent {
+            account: game_pools.key(),
+            owner: game_pools.authority,
+            cancelled_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
 
-#[event]
-pub struct EmergencyPausedEvent {
-    pub owner: Pubkey,
-    pub timestamp: i64,
-}
+        msg!("Cancelled GamePools authority transfer to {:?}", cancelled_owner);
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct InitializeGamePools<'info> {
+    // Initialize minting authority
+    pub fn initialize_minting_authority(
+        ctx: Context<InitializeMintingAuthority>,
+        max_mints_per_player_per_minute: u8,
+        is_infinite: bool,
+        max_supply: u64,
+    ) -> Result<()> {
+        let owner = ctx.accounts.owner.key();
+        ctx.accounts.authority.initialize(
+            owner,
+            max_mints_per_player_per_minute,
+            is_infinite,
+            max_supply,
+            ctx.bumps.authority,
+        );
+
+        msg!("Initialized MintingAuthority for owner: {:?}", owner);
+        Ok(())
+    }
+
+    // DISABLED: Old logic - Player eat particle = Mint token (WRONG APPROACH)
+    // This function is DISABLED because it requires player activity for owner revenue
+    // Use auto_mint_tokens() instead for scheduled, independent minting
+
+    // pub fn eat_energy_particle(...) -> DISABLED
+
+    // Create the mint split config (owner only); later changes go through the timelock
+    // Each recipient's token account is passed in remaining_accounts, in config order
+    pub fn initialize_split_config(
+        ctx: Context<InitializeSplitConfig>,
+        recipients: Vec<SplitRecipient>,
+        dust_recipient: u8,
+    ) -> Result<()> {
+        validate_split(&recipients, dust_recipient)?;
+        check_split_recipients(
+            &ctx.accounts.authority,
+            &ctx.accounts.game_token_mint.to_account_info(),
+            &recipients,
+            ctx.remaining_accounts,
+        )?;
+
+        let split_config = &mut ctx.accounts.split_config;
+        split_config.recipients = recipients;
+        split_config.dust_recipient = dust_recipient;
+        split_config.bump = ctx.bumps.split_config;
+
+        emit!(SplitConfigUpdatedEvent {
+            recipients: split_config.recipients.clone(),
+            dust_recipient,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Initialized mint split with {} recipients", split_config.recipients.len());
+        Ok(())
+    }
+
+    // Create the owner vesting escrow (owner only). From then on split recipients held
+    // by the owner are rejected, so the split config must send the owner share to the
+    // escrow's token account instead
+    pub fn initialize_owner_vesting(
+        ctx: Context<InitializeOwnerVesting>,
+        cliff_seconds: i64,
+        duration_seconds: i64,
+    ) -> Result<()> {
+        require!(
+            cliff_seconds >= 0 && duration_seconds > 0,
+            GameTokenError::InvalidVestingSchedule
+        );
+
+        let tranche_interval = VestingEscrow::tranche_interval_for(cliff_seconds, duration_seconds)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let vesting = &mut ctx.accounts.owner_vesting;
+        vesting.token_account = ctx.accounts.owner_vesting_token_account.key();
+        vesting.cliff_seconds = cliff_seconds;
+        vesting.duration_seconds = duration_seconds;
+        vesting.tranche_interval = tranche_interval;
+        vesting.tranches = Vec::new();
+        vesting.unvested = 0;
+        vesting.vested_unreleased = 0;
+        vesting.total_deposited = 0;
+        vesting.total_released = 0;
+        vesting.bump = ctx.bumps.owner_vesting;
+        ctx.accounts.authority.owner_vesting_token_account = vesting.token_account;
+
+        emit!(OwnerVestingInitializedEvent {
+            token_account: vesting.token_account,
+            cliff_seconds,
+            duration_seconds,
+            timestamp: current_time,
+        });
+
+        msg!("Initialized owner vesting: {}s cliff, {}s linear release", cliff_seconds, duration_seconds);
+        Ok(())
+    }
+
+    // Transfer everything vested so far to the current owner (permissionless)
+    pub fn release_vested(ctx: Context<ReleaseVested>) -> Result<()> {
+        require!(!ctx.accounts.authority.is_paused(PAUSE_CLAIM), GameTokenError::OperationPaused);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let vesting = &mut ctx.accounts.owner_vesting;
+        vesting.accrue(current_time);
+
+        let amount = vesting.vested_unreleased;
+        require!(amount > 0, GameTokenError::NothingToClaim);
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.owner_vesting_token_account.to_account_info(),
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: vesting.to_account_info(),
+                    mint: ctx.accounts.game_token_mint.to_account_info(),
+                },
+                &[&[
+                    b"owner_vesting",
+                    &[vesting.bump]
+                ]]
+            ),
+            amount,
+            ctx.accounts.game_token_mint.decimals,
+        )?;
+
+        vesting.vested_unreleased = 0;
+        vesting.total_released = vesting
+            .total_released
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(VestedReleasedEvent {
+            owner: ctx.accounts.authority.owner,
+            amount,
+            total_released: vesting.total_released,
+            unvested: vesting.unvested,
+            timestamp: current_time,
+        });
+
+        msg!("Released {} vested tokens to owner", amount);
+        Ok(())
+    }
+
+    // Propose a new MintingAuthority owner, who must accept with their own signature
+    pub fn propose_owner(
+        ctx: Context<TransferOwnership>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+        require!(
+            new_owner != Pubkey::default() && new_owner != authority.owner,
+            GameTokenError::InvalidNewOwner
+        );
+        authority.pending_owner = new_owner;
+
+        emit!(OwnerTransferProposedEvent {
+            account: authority.key(),
+            current_owner: authority.owner,
+            pending_owner: new_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Proposed MintingAuthority owner transfer to {:?}", new_owner);
+        Ok(())
+    }
+
+    // Pending MintingAuthority owner accepts the transfer
+    pub fn accept_owner(ctx: Context<AcceptOwner>) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+        let previous_owner = authority.owner;
+        authority.owner = ctx.accounts.new_owner.key();
+        authority.pending_owner = Pubkey::default();
+
+        emit!(OwnerTransferAcceptedEvent {
+            account: authority.key(),
+            previous_owner,
+            new_owner: authority.owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("MintingAuthority owner transferred to {:?}", authority.owner);
+        Ok(())
+    }
+
+    // Current MintingAuthority owner withdraws a pending transfer
+    pub fn cancel_owner_transfer(ctx: Context<TransferOwnership>) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+        let cancelled_owner = authority.pending_owner;
+        require!(cancelled_owner != Pubkey::default(), GameTokenError::NoPendingOwner);
+        authority.pending_owner = Pubkey::default();
+
+        emit!(OwnerTransferCancelledEvent {
+            account: authority.key(),
+            owner: authority.owner,
+            cancelled_owner,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Cancelled MintingAuthority owner transfer to {:?}", cancelled_owner);
+        Ok(())
+    }
+
+    // Queue a MintingAuthority parameter change; it can execute once
+    // `timelock_delay` seconds have passed (owner only)
+    pub fn queue_parameter_change(
+        ctx: Context<QueueParameterChange>,
+        change: ParameterChange,
+    ) -> Result<()> {
+        change.validate()?;
+        ctx.accounts.authority.check_supply_cap_change(&change)?;
+
+        let authority = &mut ctx.accounts.authority;
+        let current_time = Clock::get()?.unix_timestamp;
+        let eta = current_time
+            .checked_add(authority.timelock_delay)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        let queued = &mut ctx.accounts.queued_change;
+        queued.index = authority.timelock_count;
+        queued.change = change.clone();
+        queued.proposer = ctx.accounts.owner.key();
+        queued.queued_at = current_time;
+        queued.eta = eta;
+        queued.status = TimelockStatus::Queued;
+        queued.bump = ctx.bumps.queued_change;
+
+        authority.timelock_count = authority
+            .timelock_count
+            .checked_add(1)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(ParameterChangeQueuedEvent {
+            index: queued.index,
+            change,
+            eta,
+            timestamp: current_time,
+        });
+
+        msg!("Queued parameter change #{} executable at {}", queued.index, eta);
+        Ok(())
+    }
+
+    // Apply a queued change after its ETA and before the grace period ends (permissionless)
+    pub fn execute_parameter_change(ctx: Context<ExecuteParameterChange>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let queued = &mut ctx.accounts.queued_change;
+
+        require!(queued.status == TimelockStatus::Queued, GameTokenError::ChangeNotQueued);
+        require!(current_time >= queued.eta, GameTokenError::TimelockNotReady);
+        require!(
+            current_time <= queued.eta.saturating_add(TIMELOCK_GRACE_PERIOD),
+            GameTokenError::TimelockExpired
+        );
+
+        match &queued.change {
+            ParameterChange::MintSplit { recipients, dust_recipient } => {
+                // Recipient accounts are checked now, not when the change was queued
+                let mint = ctx
+                    .accounts
+                    .game_token_mint
+                    .as_ref()
+                    .ok_or(GameTokenError::MintAccountRequired)?;
+                check_split_recipients(
+                    &ctx.accounts.authority,
+                    &mint.to_account_info(),
+                    recipients,
+                    ctx.remaining_accounts,
+                )?;
+                let split_config = ctx
+                    .accounts
+                    .split_config
+                    .as_mut()
+                    .ok_or(GameTokenError::SplitConfigRequired)?;
+                split_config.recipients = recipients.clone();
+                split_config.dust_recipient = *dust_recipient;
+
+                emit!(SplitConfigUpdatedEvent {
+                    recipients: recipients.clone(),
+                    dust_recipient: *dust_recipient,
+                    timestamp: current_time,
+                });
+            }
+            ParameterChange::SupplyCap { is_infinite, max_supply, .. } => {
+                // The cap must also cover what the mint has actually issued
+                let mint = ctx
+                    .accounts
+                    .game_token_mint
+                    .as_ref()
+                    .ok_or(GameTokenError::MintAccountRequired)?;
+                require!(
+                    *is_infinite || *max_supply >= mint.supply,
+                    GameTokenError::InvalidParameterChange
+                );
+                ctx.accounts.authority.apply_parameter_change(&queued.change, current_time)?;
+            }
+            change => ctx.accounts.authority.apply_parameter_change(change, current_time)?,
+        }
+        queued.status = TimelockStatus::Executed;
+
+        emit!(ParameterChangeExecutedEvent {
+            index: queued.index,
+            change: queued.change.clone(),
+            timestamp: current_time,
+        });
+
+        msg!("Executed parameter change #{}", queued.index);
+        Ok(())
+    }
+
+    // Drop a queued change before it executes (owner only)
+    pub fn cancel_parameter_change(ctx: Context<CancelParameterChange>) -> Result<()> {
+        let queued = &mut ctx.accounts.queued_change;
+        require!(queued.status == TimelockStatus::Queued, GameTokenError::ChangeNotQueued);
+        queued.status = TimelockStatus::Cancelled;
+
+        emit!(ParameterChangeCancelledEvent {
+            index: queued.index,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Cancelled parameter change #{}", queued.index);
+        Ok(())
+    }
+
+    // Register a minter operator key with a lifetime mint quota (owner only)
+    pub fn add_minter(
+        ctx: Context<ManageMinters>,
+        minter: Pubkey,
+        quota: u64,
+    ) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+
+        require!(authority.minter_index(&minter).is_none(), GameTokenError::MinterAlreadyExists);
+        require!(authority.minters.len() < MAX_MINTERS, GameTokenError::TooManyMinters);
+
+        authority.minters.push(MinterQuota {
+            minter,
+            quota,
+            minted: 0,
+        });
+
+        emit!(MinterAddedEvent {
+            minter,
+            quota,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Added minter {:?} with quota {}", minter, quota);
+        Ok(())
+    }
+
+    // Remove a minter operator key (owner only)
+    pub fn remove_minter(
+        ctx: Context<ManageMinters>,
+        minter: Pubkey,
+    ) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+
+        let index = authority
+            .minter_index(&minter)
+            .ok_or(GameTokenError::MinterNotFound)?;
+        authority.minters.swap_remove(index);
+
+        emit!(MinterRemovedEvent {
+            minter,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Removed minter {:?}", minter);
+        Ok(())
+    }
+
+    // Change the lifetime quota of a registered minter (owner only)
+    pub fn set_minter_quota(
+        ctx: Context<ManageMinters>,
+        minter: Pubkey,
+        quota: u64,
+    ) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+
+        let index = authority
+            .minter_index(&minter)
+            .ok_or(GameTokenError::MinterNotFound)?;
+        let entry = &mut authority.minters[index];
+        entry.quota = quota;
+        let minted = entry.minted;
+
+        emit!(MinterQuotaUpdatedEvent {
+            minter,
+            quota,
+            minted,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Minter {:?} quota set to {} ({} already minted)", minter, quota, minted);
+        Ok(())
+    }
+
+    // Auto-mint tokens theo schedule (Logic Mới - Independent of players)
+    // Callable by the owner or by a registered minter within its quota
+    // Each split recipient's token account is passed in remaining_accounts, in config order
+    pub fn auto_mint_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AutoMintTokens<'info>>,
+        amount: u64,
+        ui_amount: bool,
+    ) -> Result<()> {
+        let amount = to_base_units(amount, ui_amount, ctx.accounts.game_token_mint.decimals)?;
+        let mint_supply = ctx.accounts.game_token_mint.supply;
+        let authority = &mut ctx.accounts.authority;
+        let game_pools = &mut ctx.accounts.game_pools;
+        let current_time = Clock::get()?.unix_timestamp;
+        let minter = ctx.accounts.minter.key();
+
+        require!(!authority.is_paused(PAUSE_MINT), GameTokenError::OperationPaused);
+        // While a schedule is active, new supply only comes from crank_emission
+        require!(!authority.emission_enabled(), GameTokenError::EmissionScheduleActive);
+
+        // Check supply limits if not infinite
+        let new_total_minted = authority
+            .total_minted
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        require!(
+            amount <= authority.remaining_supply(mint_supply),
+            GameTokenError::SupplyLimitExceeded
+        );
+
+        // Operator keys are bounded by their quota, the owner is not
+        if minter != authority.owner {
+            let index = authority
+                .minter_index(&minter)
+                .ok_or(GameTokenError::Unauthorized)?;
+            let entry = &mut authority.minters[index];
+            let minted = entry
+                .minted
+                .checked_add(amount)
+                .ok_or(GameTokenError::MathOverflow)?;
+            require!(minted <= entry.quota, GameTokenError::MinterQuotaExceeded);
+            entry.minted = minted;
+        }
+
+        // Calculate distribution from the split config, dust goes to its dust recipient
+        let split_config = &ctx.accounts.split_config;
+        let shares = split_config.compute_shares(amount)?;
+
+        mint_to_split_recipients(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.game_token_mint.to_account_info(),
+            authority.to_account_info(),
+            authority,
+            split_config,
+            &shares,
+            ctx.remaining_accounts,
+        )?;
+
+        // Update tracking, game pool share becomes playable balance
+        authority.total_minted = new_total_minted;
+        let game_amount = game_pools.credit_minted_shares(
+            split_config,
+            &shares,
+            &ctx.accounts.game_pools_token_account.key(),
+        )?;
+        let vested_amount = record_owner_vesting_deposit(
+            authority,
+            ctx.accounts.owner_vesting.as_deref_mut(),
+            split_config,
+            &shares,
+            current_time,
+        )?;
+        let released_amount = owner_released_share(&authority.owner, &shares, ctx.remaining_accounts)?;
+
+        // Emit event
+        emit!(AutoMintEvent {
+            minter,
+            amount,
+            game_amount,
+            vested_amount,
+            released_amount,
+            shares,
+            timestamp: current_time,
+        });
+
+        msg!("Auto-minted {} tokens: {} to game pool", amount, game_amount);
+        Ok(())
+    }
+
+    // Mint whatever the on-chain emission schedule has made due since the last
+    // crank (permissionless). Split recipients go in remaining_accounts, as for auto_mint_tokens
+    pub fn crank_emission<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankEmission<'info>>,
+    ) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+        let game_pools = &mut ctx.accounts.game_pools;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(!authority.is_paused(PAUSE_MINT), GameTokenError::OperationPaused);
+        require!(authority.emission_enabled(), GameTokenError::EmissionNotConfigured);
+
+        let from = authority.last_mint_timestamp;
+        let (due, settled_until) = authority.emission_due(current_time)?;
+
+        // Emission beyond a finite supply cap is forfeited, not deferred
+        let amount = due.min(authority.remaining_supply(ctx.accounts.game_token_mint.supply));
+
+        let split_config = &ctx.accounts.split_config;
+        let shares = split_config.compute_shares(amount)?;
+        mint_to_split_recipients(
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.game_token_mint.to_account_info(),
+            authority.to_account_info(),
+            authority,
+            split_config,
+            &shares,
+            ctx.remaining_accounts,
+        )?;
+
+        // Update tracking
+        authority.total_minted = authority
+            .total_minted
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        authority.last_mint_timestamp = settled_until;
+        let game_amount = game_pools.credit_minted_shares(
+            split_config,
+            &shares,
+            &ctx.accounts.game_pools_token_account.key(),
+        )?;
+        let vested_amount = record_owner_vesting_deposit(
+            authority,
+            ctx.accounts.owner_vesting.as_deref_mut(),
+            split_config,
+            &shares,
+            current_time,
+        )?;
+        let released_amount = owner_released_share(&authority.owner, &shares, ctx.remaining_accounts)?;
+
+        emit!(EmissionCrankedEvent {
+            cranker: ctx.accounts.cranker.key(),
+            amount,
+            forfeited: due - amount,
+            game_amount,
+            vested_amount,
+            released_amount,
+            shares,
+            from,
+            to: settled_until,
+            epoch: authority.emission_epoch_at(settled_until),
+            timestamp: current_time,
+        });
+
+        msg!("Emission crank minted {} tokens for {}..{}", amount, from, settled_until);
+        Ok(())
+    }
+
+    // CORE CONCEPT: Player earns from pre-minted game pool (CORRECT APPROACH)
+    // Game pool is filled by auto_mint_tokens() scheduler - independent of players
+    // Players only earn from existing pool balance; earnings are reserved out of the
+    // active pool as a claimable balance and paid out by player_claim_tokens
+    // Each earn must carry a voucher signed by the game server, verified through
+    // an ed25519 program instruction placed right before this one
+    pub fn player_earn_from_pool(
+        ctx: Context<PlayerEarnFromPool>,
+        amount: u64,
+        ui_amount: bool,
+        nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
+        // The voucher always covers the amount in base units
+        let amount = to_base_units(amount, ui_amount, ctx.accounts.game_token_mint.decimals)?;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(!ctx.accounts.authority.is_paused(PAUSE_EARN), GameTokenError::OperationPaused);
+
+        // Verify the server voucher before touching the pool
+        let game_server = ctx.accounts.game_pools.game_server;
+        require!(game_server != Pubkey::default(), GameTokenError::GameServerNotSet);
+        require!(current_time <= expiry, GameTokenError::VoucherExpired);
+        require!(
+            nonce > ctx.accounts.player_stats.last_voucher_nonce,
+            GameTokenError::VoucherNonceUsed
+        );
+        let message = earn_voucher_message(&ctx.accounts.player.key(), amount, nonce, expiry);
+        verify_ed25519_instruction(&ctx.accounts.instructions, &game_server, &message)?;
+
+        // Rate limiting cho player earn
+        ctx.accounts
+            .player_stats
+            .apply_rate_limits(&ctx.accounts.authority, amount, current_time)?;
+
+        // Global earn budget shared by all players for the current epoch
+        ctx.accounts
+            .game_pools
+            .consume_earn_budget(amount, current_time)?;
+
+        // Check if pool has enough tokens (pool filled by auto-mint scheduler)
+        require!(ctx.accounts.game_pools.active_pool >= amount, GameTokenError::InsufficientPool);
+
+        // Earnings are only paid inside an open session and under its cap
+        ctx.accounts
+            .player_session
+            .record_earn(amount, current_time)?;
+
+        // Attribute the earning to the open season, within its time window and budget
+        if ctx.accounts.game_pools.season_active {
+            let season = ctx
+                .accounts
+                .season
+                .as_mut()
+                .ok_or(GameTokenError::SeasonAccountsRequired)?;
+            let season_stats = ctx
+                .accounts
+                .player_season_stats
+                .as_mut()
+                .ok_or(GameTokenError::SeasonAccountsRequired)?;
+            if season_stats.player == Pubkey::default() {
+                season_stats.player = ctx.accounts.player.key();
+                season_stats.season_id = season.id;
+                season_stats.bump = ctx.bumps.player_season_stats.unwrap_or_default();
+            }
+            let attributed = season.record_earn(season_stats, amount, current_time)?;
+            if attributed && season.leaderboard_enabled {
+                let leaderboard = ctx
+                    .accounts
+                    .leaderboard
+                    .as_mut()
+                    .ok_or(GameTokenError::LeaderboardAccountRequired)?;
+                leaderboard.record(ctx.accounts.player.key(), season_stats.tokens_earned);
+            }
+        }
+
+        // Reserve the earning out of the active pool; the tokens stay in the pool
+        // token account until the player claims them
+        let game_pools = &mut ctx.accounts.game_pools;
+        let player_stats = &mut ctx.accounts.player_stats;
+        if player_stats.player == Pubkey::default() {
+            player_stats.player = ctx.accounts.player.key();
+            player_stats.bump = ctx.bumps.player_stats;
+        }
+        game_pools.active_pool -= amount;
+        game_pools.outstanding_claims = game_pools
+            .outstanding_claims
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        player_stats.total_earned = player_stats
+            .total_earned
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        player_stats.last_voucher_nonce = nonce;
+
+        emit!(PlayerEarnedEvent {
+            player: ctx.accounts.player.key(),
+            amount,
+            nonce,
+            claimable: player_stats.claimable(),
+            remaining_pool: game_pools.active_pool,
+            remaining_earn_budget: game_pools.remaining_earn_budget(current_time),
+            timestamp: current_time,
+        });
+
+        // Referral bonus on earnings, unless it is configured to be paid on claims
+        if !ctx.accounts.game_pools.referral_pay_on_claim {
+            ctx.accounts.referral_bonus.pay(
+                &mut ctx.accounts.game_pools,
+                &ctx.accounts.player_stats,
+                &ctx.accounts.game_token_mint,
+                &ctx.accounts.token_program,
+                amount,
+                current_time,
+            )?;
+        }
+
+        msg!("Player {} earned {} claimable tokens from auto-filled game pool", ctx.accounts.player.key(), amount);
+        Ok(())
+    }
+
+    // DISABLED: Legacy earn_tokens function (WRONG APPROACH)
+    // This function is DISABLED - use player_earn_from_pool instead
+    // player_earn_from_pool has proper player tracking and rate limiting
+
+    // pub fn earn_tokens(...) -> DISABLED
+
+    // Player claim tokens from treasury (User-specified amount)
+    pub fn player_claim_tokens(
+        ctx: Context<PlayerClaimTokens>,
+        amount: u64,
+        ui_amount: bool,
+    ) -> Result<()> {
+        let amount = to_base_units(amount, ui_amount, ctx.accounts.game_token_mint.decimals)?;
+        let game_pools_bump = ctx.accounts.game_pools.bump;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        require!(!ctx.accounts.authority.is_paused(PAUSE_CLAIM), GameTokenError::OperationPaused);
+
+        // Players can only withdraw what they have earned and not yet claimed
+        require!(amount > 0, GameTokenError::NothingToClaim);
+        require!(
+            amount <= ctx.accounts.player_stats.claimable(),
+            GameTokenError::ClaimExceedsEarned
+        );
+        let fee = transfer_fee_for(&ctx.accounts.game_token_mint, amount)?;
+        let net_amount = amount - fee;
+
+        // Transfer từ treasury (game_pools_token_account) cho player
+        // Player tự trả phí transaction
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.game_pools_token_account.to_account_info(),
+                    to: ctx.accounts.player_token_account.to_account_info(),
+                    authority: ctx.accounts.game_pools.to_account_info(),
+                    mint: ctx.accounts.game_token_mint.to_account_info(),
+                },
+                &[&[
+                    b"game_pools",
+                    &[game_pools_bump]
+                ]]
+            ),
+            amount,
+            ctx.accounts.game_token_mint.decimals,
+        )?;
+
+        // Update tracking, the amount was reserved out of active_pool when earned
+        let game_pools = &mut ctx.accounts.game_pools;
+        let player_stats = &mut ctx.accounts.player_stats;
+        game_pools.outstanding_claims -= amount;
+        player_stats.total_claimed += amount;
+        player_stats.total_transfer_fees = player_stats
+            .total_transfer_fees
+            .checked_add(fee)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        // Emit claim event
+        emit!(PlayerClaimedEvent {
+            player: ctx.accounts.player.key(),
+            amount,
+            fee,
+            net_amount,
+            remaining_claimable: player_stats.claimable(),
+            outstanding_claims: game_pools.outstanding_claims,
+            timestamp: current_time,
+        });
+
+        if ctx.accounts.game_pools.referral_pay_on_claim {
+            ctx.accounts.referral_bonus.pay(
+                &mut ctx.accounts.game_pools,
+                &ctx.accounts.player_stats,
+                &ctx.accounts.game_token_mint,
+                &ctx.accounts.token_program,
+                amount,
+                current_time,
+            )?;
+        }
+
+        msg!("Player {} claimed {} tokens from treasury", ctx.accounts.player.key(), amount);
+        Ok(())
+    }
+
+    // Emergency pause (owner only)
+    // Sets the given PAUSE_* flags; PAUSE_GLOBAL stops mint, earn and claim at once
+    pub fn emergency_pause(
+        ctx: Context<EmergencyControl>,
+        pause_flags: u8,
+        reason_code: u16,
+    ) -> Result<()> {
+        require!(
+            pause_flags != 0 && pause_flags & !PAUSE_FLAGS_MASK == 0,
+            GameTokenError::InvalidPauseFlags
+        );
+
+        let authority = &mut ctx.accounts.authority;
+        let current_time = Clock::get()?.unix_timestamp;
+        authority.pause_flags |= pause_flags;
+        authority.pause_reason = reason_code;
+        authority.paused_at = current_time;
+
+        emit!(EmergencyPausedEvent {
+            owner: ctx.accounts.owner.key(),
+            pause_flags: authority.pause_flags,
+            reason_code,
+            timestamp: current_time,
+        });
+
+        msg!("Emergency pause activated by owner: flags {:#04x}, reason {}", authority.pause_flags, reason_code);
+        Ok(())
+    }
+
+    // Clear the given PAUSE_* flags (owner only)
+    pub fn unpause(
+        ctx: Context<EmergencyControl>,
+        pause_flags: u8,
+    ) -> Result<()> {
+        require!(
+            pause_flags != 0 && pause_flags & !PAUSE_FLAGS_MASK == 0,
+            GameTokenError::InvalidPauseFlags
+        );
+
+        let authority = &mut ctx.accounts.authority;
+        require!(authority.pause_flags & pause_flags != 0, GameTokenError::NotPaused);
+        authority.pause_flags &= !pause_flags;
+        if authority.pause_flags == 0 {
+            authority.pause_reason = 0;
+            authority.paused_at = 0;
+        }
+
+        emit!(UnpausedEvent {
+            owner: ctx.accounts.owner.key(),
+            cleared_flags: pause_flags,
+            pause_flags: authority.pause_flags,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Unpaused flags {:#04x}, remaining {:#04x}", pause_flags, authority.pause_flags);
+        Ok(())
+    }
+
+    // Create an M-of-N multisig. Its signer PDA can be proposed as owner of
+    // MintingAuthority / GameTokenPools and then accept through a multisig transaction
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        create_key: Pubkey,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_multisig_owners(&owners, threshold)?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.create_key = create_key;
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        multisig.owner_set_seqno = 0;
+        multisig.transaction_count = 0;
+        multisig.bump = ctx.bumps.multisig;
+        multisig.signer_bump = ctx.bumps.multisig_signer;
+
+        emit!(MultisigCreatedEvent {
+            multisig: multisig.key(),
+            multisig_signer: ctx.accounts.multisig_signer.key(),
+            owners: multisig.owners.clone(),
+            threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Created {}-of-{} multisig {:?}", threshold, multisig.owners.len(), multisig.key());
+        Ok(())
+    }
+
+    // Propose an instruction to be signed by the multisig signer PDA (owners only)
+    // The proposer's approval is recorded immediately
+    pub fn propose_transaction(
+        ctx: Context<ProposeTransaction>,
+        program_id: Pubkey,
+        accounts: Vec<TransactionAccount>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        require!(accounts.len() <= MAX_MULTISIG_TX_ACCOUNTS, GameTokenError::TransactionTooLarge);
+        require!(data.len() <= MAX_MULTISIG_TX_DATA, GameTokenError::TransactionTooLarge);
+
+        let multisig = &mut ctx.accounts.multisig;
+        let signers = multisig.proposal_signers(&ctx.accounts.proposer.key())?;
+
+        let transaction = &mut ctx.accounts.transaction;
+        transaction.multisig = multisig.key();
+        transaction.index = multisig.transaction_count;
+        transaction.proposer = ctx.accounts.proposer.key();
+        transaction.program_id = program_id;
+        transaction.accounts = accounts;
+        transaction.data = data;
+        transaction.signers = signers;
+        transaction.owner_set_seqno = multisig.owner_set_seqno;
+        transaction.did_execute = false;
+        transaction.bump = ctx.bumps.transaction;
+
+        multisig.transaction_count = multisig
+            .transaction_count
+            .checked_add(1)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(TransactionProposedEvent {
+            multisig: multisig.key(),
+            transaction: transaction.key(),
+            index: transaction.index,
+            proposer: transaction.proposer,
+            program_id,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Proposed multisig transaction #{} for program {:?}", transaction.index, program_id);
+        Ok(())
+    }
+
+    // Record an owner's approval of a pending multisig transaction
+    pub fn approve_transaction(ctx: Context<ApproveTransaction>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let transaction = &mut ctx.accounts.transaction;
+        let approvals = transaction.approve(multisig, &ctx.accounts.owner.key())?;
+
+        emit!(TransactionApprovedEvent {
+            multisig: multisig.key(),
+            transaction: transaction.key(),
+            owner: ctx.accounts.owner.key(),
+            approvals,
+            threshold: multisig.threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Multisig transaction #{} approved ({}/{})", transaction.index, approvals, multisig.threshold);
+        Ok(())
+    }
+
+    // Execute a multisig transaction once it has reached the threshold (permissionless)
+    // Every account of the proposed instruction, plus its program, goes in remaining_accounts
+    pub fn execute_transaction(ctx: Context<ExecuteTransaction>) -> Result<()> {
+        let multisig = &ctx.accounts.multisig;
+        let transaction = &mut ctx.accounts.transaction;
+        transaction.check_executable(multisig)?;
+
+        let multisig_key = multisig.key();
+        let multisig_signer = ctx.accounts.multisig_signer.key();
+        let instruction = Instruction {
+            program_id: transaction.program_id,
+            accounts: transaction
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer || account.pubkey == multisig_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: transaction.data.clone(),
+        };
+
+        transaction.did_execute = true;
+        invoke_signed(
+            &instruction,
+            ctx.remaining_accounts,
+            &[&[
+                b"multisig_signer",
+                multisig_key.as_ref(),
+                &[multisig.signer_bump]
+            ]],
+        )?;
+
+        emit!(TransactionExecutedEvent {
+            multisig: multisig_key,
+            transaction: transaction.key(),
+            index: transaction.index,
+            executor: ctx.accounts.executor.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Executed multisig transaction #{}", transaction.index);
+        Ok(())
+    }
+
+    // Replace the owner set and threshold (multisig signer only, i.e. via execute_transaction)
+    // Bumps the owner set sequence so pending proposals can no longer execute
+    pub fn set_multisig_owners(
+        ctx: Context<MultisigAuth>,
+        owners: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        validate_multisig_owners(&owners, threshold)?;
+
+        let multisig = &mut ctx.accounts.multisig;
+        multisig.owners = owners;
+        multisig.threshold = threshold;
+        multisig.owner_set_seqno = multisig
+            .owner_set_seqno
+            .checked_add(1)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(MultisigOwnersChangedEvent {
+            multisig: multisig.key(),
+            owners: multisig.owners.clone(),
+            threshold,
+            owner_set_seqno: multisig.owner_set_seqno,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Multisig owners set to {}-of-{}", threshold, multisig.owners.len());
+        Ok(())
+    }
+
+    // Create the staking pool and its vault (pools authority only)
+    pub fn initialize_staking(
+        ctx: Context<InitializeStaking>,
+        apr_bps: u16,
+        unstake_cooldown: i64,
+    ) -> Result<()> {
+        require!(unstake_cooldown >= 0, GameTokenError::InvalidStakingParams);
+
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        stake_pool.vault = ctx.accounts.stake_vault.key();
+        stake_pool.total_staked = 0;
+        stake_pool.reward_per_token_stored = 0;
+        stake_pool.last_update_time = Clock::get()?.unix_timestamp;
+        stake_pool.apr_bps = apr_bps;
+        stake_pool.unstake_cooldown = unstake_cooldown;
+        stake_pool.bump = ctx.bumps.stake_pool;
+        stake_pool.vault_bump = ctx.bumps.stake_vault;
+
+        emit!(StakingParamsUpdatedEvent {
+            apr_bps,
+            unstake_cooldown,
+            timestamp: stake_pool.last_update_time,
+        });
+
+        msg!("Initialized staking at {} bps APR, {}s cooldown", apr_bps, unstake_cooldown);
+        Ok(())
+    }
+
+    // Change APR and cooldown (pools authority only); accrued rewards keep the old APR
+    pub fn set_staking_params(
+        ctx: Context<SetStakingParams>,
+        apr_bps: u16,
+        unstake_cooldown: i64,
+    ) -> Result<()> {
+        require!(unstake_cooldown >= 0, GameTokenError::InvalidStakingParams);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        stake_pool.update_rewards(current_time);
+        stake_pool.apr_bps = apr_bps;
+        stake_pool.unstake_cooldown = unstake_cooldown;
+
+        emit!(StakingParamsUpdatedEvent {
+            apr_bps,
+            unstake_cooldown,
+            timestamp: current_time,
+        });
+
+        msg!("Staking params set: {} bps APR, {}s cooldown", apr_bps, unstake_cooldown);
+        Ok(())
+    }
+
+    // Player stakes game tokens into the program vault
+    pub fn stake(ctx: Context<Stake>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.authority.is_paused(PAUSE_EARN), GameTokenError::OperationPaused);
+        require!(amount > 0, GameTokenError::InvalidStakeAmount);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        let player_stake = &mut ctx.accounts.player_stake;
+        if player_stake.player == Pubkey::default() {
+            player_stake.player = ctx.accounts.player.key();
+            player_stake.bump = ctx.bumps.player_stake;
+        }
+
+        stake_pool.update_rewards(current_time);
+        player_stake.settle_rewards(stake_pool)?;
+
+        // The vault receives the amount net of any Token-2022 transfer fee
+        let fee = transfer_fee_for(&ctx.accounts.game_token_mint, amount)?;
+        let staked = amount - fee;
+        require!(staked > 0, GameTokenError::InvalidStakeAmount);
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.player_token_account.to_account_info(),
+                    to: ctx.accounts.stake_vault.to_account_info(),
+                    authority: ctx.accounts.player.to_account_info(),
+                    mint: ctx.accounts.game_token_mint.to_account_info(),
+                },
+            ),
+            amount,
+            ctx.accounts.game_token_mint.decimals,
+        )?;
+
+        player_stake.amount = player_stake
+            .amount
+            .checked_add(staked)
+            .ok_or(GameTokenError::MathOverflow)?;
+        stake_pool.total_staked = stake_pool
+            .total_staked
+            .checked_add(staked)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(StakedEvent {
+            player: player_stake.player,
+            amount,
+            fee,
+            staked: player_stake.amount,
+            total_staked: stake_pool.total_staked,
+            timestamp: current_time,
+        });
+
+        msg!("Player {} staked {} tokens (fee {})", player_stake.player, staked, fee);
+        Ok(())
+    }
+
+    // Stop earning on `amount`. Without a cooldown the tokens are returned at once,
+    // otherwise they become withdrawable through withdraw_unstaked after the cooldown
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        require!(!ctx.accounts.authority.is_paused(PAUSE_CLAIM), GameTokenError::OperationPaused);
+        require!(amount > 0, GameTokenError::InvalidStakeAmount);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        let player_stake = &mut ctx.accounts.player_stake;
+        require!(player_stake.amount >= amount, GameTokenError::InsufficientStake);
+
+        stake_pool.update_rewards(current_time);
+        player_stake.settle_rewards(stake_pool)?;
+
+        player_stake.amount -= amount;
+        stake_pool.total_staked -= amount;
+
+        let available_at = current_time
+            .checked_add(stake_pool.unstake_cooldown)
+            .ok_or(GameTokenError::MathOverflow)?;
+        player_stake.request_unstake(amount, available_at)?;
+
+        emit!(UnstakeRequestedEvent {
+            player: player_stake.player,
+            amount,
+            staked: player_stake.amount,
+            available_at,
+            timestamp: current_time,
+        });
+
+        msg!("Player {} unstaked {} tokens, withdrawable at {}", player_stake.player, amount, available_at);
+
+        if stake_pool.unstake_cooldown == 0 {
+            withdraw_unstaked_tokens(ctx.accounts, current_time)?;
+        }
+        Ok(())
+    }
+
+    // Withdraw tokens whose unstake cooldown has passed
+    pub fn withdraw_unstaked(ctx: Context<Unstake>) -> Result<()> {
+        require!(!ctx.accounts.authority.is_paused(PAUSE_CLAIM), GameTokenError::OperationPaused);
+        let current_time = Clock::get()?.unix_timestamp;
+        withdraw_unstaked_tokens(ctx.accounts, current_time)
+    }
+
+    // Pay accrued staking rewards out of the reward pool
+    // If the reward pool runs short, the unpaid part stays pending
+    pub fn claim_stake_rewards(ctx: Context<ClaimStakeRewards>) -> Result<()> {
+        require!(!ctx.accounts.authority.is_paused(PAUSE_CLAIM), GameTokenError::OperationPaused);
+
+        let game_pools_bump = ctx.accounts.game_pools.bump;
+        let current_time = Clock::get()?.unix_timestamp;
+        let stake_pool = &mut ctx.accounts.stake_pool;
+        let player_stake = &mut ctx.accounts.player_stake;
+
+        stake_pool.update_rewards(current_time);
+        player_stake.settle_rewards(stake_pool)?;
+
+        let amount = player_stake.pending_rewards.min(ctx.accounts.game_pools.reward_pool);
+        require!(amount > 0, GameTokenError::NothingToClaim);
+        let fee = transfer_fee_for(&ctx.accounts.game_token_mint, amount)?;
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.reward_pool_token_account.to_account_info(),
+                    to: ctx.accounts.player_token_account.to_account_info(),
+                    authority: ctx.accounts.game_pools.to_account_info(),
+                    mint: ctx.accounts.game_token_mint.to_account_info(),
+                },
+                &[&[
+                    b"game_pools",
+                    &[game_pools_bump]
+                ]]
+            ),
+            amount,
+            ctx.accounts.game_token_mint.decimals,
+        )?;
+
+        let game_pools = &mut ctx.accounts.game_pools;
+        game_pools.reward_pool -= amount;
+        player_stake.pending_rewards -= amount;
+        player_stake.total_rewards_claimed = player_stake
+            .total_rewards_claimed
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(StakeRewardsClaimedEvent {
+            player: player_stake.player,
+            amount,
+            fee,
+            pending_rewards: player_stake.pending_rewards,
+            remaining_reward_pool: game_pools.reward_pool,
+            timestamp: current_time,
+        });
+
+        msg!("Player {} claimed {} staking rewards", player_stake.player, amount);
+        Ok(())
+    }
+
+    // Sweep withheld Token-2022 transfer fees into the burn or treasury (active) pool
+    // Token accounts holding withheld fees are passed as remaining_accounts; the
+    // game_pools PDA must be the mint's withdraw-withheld authority
+    pub fn harvest_transfer_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, HarvestTransferFees<'info>>,
+        destination: PoolKind,
+    ) -> Result<()> {
+        let game_pools_bump = ctx.accounts.game_pools.bump;
+        let destination_info = ctx.accounts.fee_destination(destination)?.to_account_info();
+        let balance_before = ctx.accounts.fee_destination(destination)?.amount;
+
+        if !ctx.remaining_accounts.is_empty() {
+            anchor_spl::token_2022_extensions::transfer_fee::harvest_withheld_tokens_to_mint(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    HarvestWithheldTokensToMint {
+                        token_program_id: ctx.accounts.token_program.to_account_info(),
+                        mint: ctx.accounts.game_token_mint.to_account_info(),
+                    },
+                ),
+                ctx.remaining_accounts.to_vec(),
+            )?;
+        }
+
+        anchor_spl::token_2022_extensions::transfer_fee::withdraw_withheld_tokens_from_mint(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                WithdrawWithheldTokensFromMint {
+                    token_program_id: ctx.accounts.token_program.to_account_info(),
+                    mint: ctx.accounts.game_token_mint.to_account_info(),
+                    destination: destination_info,
+                    authority: ctx.accounts.game_pools.to_account_info(),
+                },
+                &[&[
+                    b"game_pools",
+                    &[game_pools_bump]
+                ]]
+            ),
+        )?;
+
+        let destination_account = ctx.accounts.fee_destination(destination)?;
+        destination_account.reload()?;
+        let harvested = destination_account
+            .amount
+            .checked_sub(balance_before)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        let game_pools = &mut ctx.accounts.game_pools;
+        let pool_balance = game_pools.pool_balance_mut(destination);
+        *pool_balance = pool_balance
+            .checked_add(harvested)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(TransferFeesHarvestedEvent {
+            destination,
+            amount: harvested,
+            pool_balance: game_pools.pool_balance(destination),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Harvested {} withheld fee tokens into {:?} pool", harvested, destination);
+        Ok(())
+    }
+
+    // Permanently lock max_supply (owner only); later SupplyCap changes can only lower it
+    pub fn lock_max_supply(ctx: Context<LockMaxSupply>) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+        require!(!authority.is_infinite, GameTokenError::InvalidParameterChange);
+        require!(!authority.max_supply_locked, GameTokenError::SupplyCapLocked);
+        authority.max_supply_locked = true;
+
+        emit!(MaxSupplyLockedEvent {
+            max_supply: authority.max_supply,
+            cap_mode: authority.supply_cap_mode,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Max supply locked at {}", authority.max_supply);
+        Ok(())
+    }
+
+    // Compare tracked totals with the mint's real supply (permissionless). A shortfall
+    // is recorded as burned; any excess (minted outside this program) is only reported
+    pub fn reconcile_supply(ctx: Context<ReconcileSupply>) -> Result<()> {
+        let authority = &mut ctx.accounts.authority;
+        let mint_supply = ctx.accounts.game_token_mint.supply;
+        let current_time = Clock::get()?.unix_timestamp;
+
+        let expected_supply = authority.total_minted.saturating_sub(authority.total_burned);
+        let drift = mint_supply as i128 - expected_supply as i128;
+        let newly_burned = expected_supply.saturating_sub(mint_supply);
+        authority.total_burned = authority
+            .total_burned
+            .checked_add(newly_burned)
+            .ok_or(GameTokenError::MathOverflow)?;
+        authority.last_reconciled_at = current_time;
+
+        emit!(SupplyReconciledEvent {
+            mint_supply,
+            total_minted: authority.total_minted,
+            total_burned: authority.total_burned,
+            newly_burned,
+            unaccounted_supply: mint_supply.saturating_sub(expected_supply),
+            drift: drift.clamp(i64::MIN as i128, i64::MAX as i128) as i64,
+            timestamp: current_time,
+        });
+
+        msg!(
+            "Reconciled supply: mint {}, tracked {}, newly burned {}",
+            mint_supply,
+            expected_supply,
+            newly_burned
+        );
+        Ok(())
+    }
+
+    // Per-session earn cap, maximum session length and daily cap across sessions
+    // (pools authority only), 0 disables
+    pub fn set_session_config(
+        ctx: Context<ManageGamePools>,
+        session_earn_cap: u64,
+        max_session_duration: i64,
+        session_daily_cap: u64,
+    ) -> Result<()> {
+        require!(max_session_duration >= 0, GameTokenError::InvalidSessionConfig);
+
+        let game_pools = &mut ctx.accounts.game_pools;
+        game_pools.session_earn_cap = session_earn_cap;
+        game_pools.max_session_duration = max_session_duration;
+        game_pools.session_daily_cap = session_daily_cap;
+
+        emit!(SessionConfigUpdatedEvent {
+            session_earn_cap,
+            max_session_duration,
+            session_daily_cap,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!(
+            "Session config set: cap {}, max duration {}s",
+            session_earn_cap,
+            max_session_duration
+        );
+        Ok(())
+    }
+
+    // Open a game session; player_earn_from_pool only pays inside an open session
+    pub fn start_session(ctx: Context<StartSession>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let game_pools = &ctx.accounts.game_pools;
+        let player_stats = &mut ctx.accounts.player_stats;
+        if player_stats.player == Pubkey::default() {
+            player_stats.player = ctx.accounts.player.key();
+            player_stats.bump = ctx.bumps.player_stats;
+        }
+
+        let session = &mut ctx.accounts.player_session;
+        if session.player == Pubkey::default() {
+            session.player = ctx.accounts.player.key();
+            session.bump = ctx.bumps.player_session;
+        }
+        require!(!session.is_open, GameTokenError::SessionAlreadyOpen);
+
+        session.is_open = true;
+        session.started_at = current_time;
+        session.expires_at = if game_pools.max_session_duration > 0 {
+            current_time
+                .checked_add(game_pools.max_session_duration)
+                .ok_or(GameTokenError::MathOverflow)?
+        } else {
+            0
+        };
+        session.earn_cap = game_pools.session_earn_cap;
+        session.daily_cap = game_pools.session_daily_cap;
+        session.tokens_earned = 0;
+        session.session_count = session
+            .session_count
+            .checked_add(1)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(SessionStartedEvent {
+            player: session.player,
+            session_id: session.session_count,
+            earn_cap: session.earn_cap,
+            expires_at: session.expires_at,
+            timestamp: current_time,
+        });
+
+        msg!("Player {} started session #{}", session.player, session.session_count);
+        Ok(())
+    }
+
+    // Close the open session and roll its totals into the player's lifetime stats
+    pub fn end_session(ctx: Context<EndSession>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let session = &mut ctx.accounts.player_session;
+        require!(session.is_open, GameTokenError::SessionNotOpen);
+        session.is_open = false;
+
+        // An expired session only counts up to its expiry
+        let ended_at = if session.expires_at > 0 {
+            current_time.min(session.expires_at)
+        } else {
+            current_time
+        };
+        let duration = ended_at.saturating_sub(session.started_at).max(0) as u64;
+
+        let player_stats = &mut ctx.accounts.player_stats;
+        player_stats.session_tokens = player_stats
+            .session_tokens
+            .checked_add(session.tokens_earned)
+            .ok_or(GameTokenError::MathOverflow)?;
+        player_stats.sessions_completed = player_stats
+            .sessions_completed
+            .checked_add(1)
+            .ok_or(GameTokenError::MathOverflow)?;
+        player_stats.total_session_seconds = player_stats
+            .total_session_seconds
+            .checked_add(duration)
+            .ok_or(GameTokenError::MathOverflow)?;
+        player_stats.best_session_tokens = player_stats
+            .best_session_tokens
+            .max(session.tokens_earned);
+
+        emit!(SessionEndedEvent {
+            player: session.player,
+            session_id: session.session_count,
+            started_at: session.started_at,
+            ended_at,
+            tokens_earned: session.tokens_earned,
+            earn_cap: session.earn_cap,
+            sessions_completed: player_stats.sessions_completed,
+            timestamp: current_time,
+        });
+
+        msg!(
+            "Player {} ended session #{} with {} tokens",
+            session.player,
+            session.session_count,
+            session.tokens_earned
+        );
+        Ok(())
+    }
+
+    // Define an achievement and its one-time reward (pools authority only)
+    // expires_at of 0 means the achievement never expires
+    pub fn create_achievement(
+        ctx: Context<CreateAchievement>,
+        achievement_id: u16,
+        reward_amount: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        require!(
+            (achievement_id as usize) < MAX_ACHIEVEMENTS && expires_at >= 0,
+            GameTokenError::InvalidAchievement
+        );
+
+        let achievement = &mut ctx.accounts.achievement;
+        achievement.id = achievement_id;
+        achievement.reward_amount = reward_amount;
+        achievement.expires_at = expires_at;
+        achievement.is_active = true;
+        achievement.unlock_count = 0;
+        achievement.bump = ctx.bumps.achievement;
+
+        emit!(AchievementUpdatedEvent {
+            achievement_id,
+            reward_amount,
+            expires_at,
+            is_active: true,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Created achievement #{} with reward {}", achievement_id, reward_amount);
+        Ok(())
+    }
+
+    // Change an achievement's reward, expiry or active flag (pools authority only)
+    pub fn update_achievement(
+        ctx: Context<UpdateAchievement>,
+        reward_amount: u64,
+        expires_at: i64,
+        is_active: bool,
+    ) -> Result<()> {
+        require!(expires_at >= 0, GameTokenError::InvalidAchievement);
+
+        let achievement = &mut ctx.accounts.achievement;
+        achievement.reward_amount = reward_amount;
+        achievement.expires_at = expires_at;
+        achievement.is_active = is_active;
+
+        emit!(AchievementUpdatedEvent {
+            achievement_id: achievement.id,
+            reward_amount,
+            expires_at,
+            is_active,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Updated achievement #{}", achievement.id);
+        Ok(())
+    }
+
+    // Unlock an achievement for the player and pay its reward from the reward pool,
+    // at most once per player. Requires an ed25519 attestation by the game server
+    // over achievement_voucher_message, placed right before this instruction
+    pub fn unlock_achievement(ctx: Context<UnlockAchievement>) -> Result<()> {
+        require!(!ctx.accounts.authority.is_paused(PAUSE_EARN), GameTokenError::OperationPaused);
+
+        let game_pools_bump = ctx.accounts.game_pools.bump;
+        let current_time = Clock::get()?.unix_timestamp;
+        let player = ctx.accounts.player.key();
+        let achievement = &ctx.accounts.achievement;
+
+        require!(achievement.is_active, GameTokenError::AchievementInactive);
+        require!(
+            achievement.expires_at == 0 || current_time <= achievement.expires_at,
+            GameTokenError::AchievementExpired
+        );
+
+        let game_server = ctx.accounts.game_pools.game_server;
+        require!(game_server != Pubkey::default(), GameTokenError::GameServerNotSet);
+        let message = achievement_voucher_message(&player, achievement.id);
+        verify_ed25519_instruction(&ctx.accounts.instructions, &game_server, &message)?;
+
+        let player_achievements = &mut ctx.accounts.player_achievements;
+        if player_achievements.player == Pubkey::default() {
+            player_achievements.player = player;
+            player_achievements.bump = ctx.bumps.player_achievements;
+        }
+        require!(
+            !player_achievements.is_unlocked(achievement.id),
+            GameTokenError::AchievementAlreadyUnlocked
+        );
+        player_achievements.unlock(achievement.id);
+
+        let amount = achievement.reward_amount;
+        require!(ctx.accounts.game_pools.reward_pool >= amount, GameTokenError::InsufficientPool);
+        let fee = transfer_fee_for(&ctx.accounts.game_token_mint, amount)?;
+        if amount > 0 {
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.reward_pool_token_account.to_account_info(),
+                        to: ctx.accounts.player_token_account.to_account_info(),
+                        authority: ctx.accounts.game_pools.to_account_info(),
+                        mint: ctx.accounts.game_token_mint.to_account_info(),
+                    },
+                    &[&[
+                        b"game_pools",
+                        &[game_pools_bump]
+                    ]]
+                ),
+                amount,
+                ctx.accounts.game_token_mint.decimals,
+            )?;
+        }
+
+        let game_pools = &mut ctx.accounts.game_pools;
+        game_pools.reward_pool -= amount;
+        let achievement = &mut ctx.accounts.achievement;
+        achievement.unlock_count = achievement
+            .unlock_count
+            .checked_add(1)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(AchievementUnlockedEvent {
+            player,
+            achievement_id: achievement.id,
+            reward_amount: amount,
+            fee,
+            unlocked_count: player_achievements.unlocked_count,
+            remaining_reward_pool: game_pools.reward_pool,
+            timestamp: current_time,
+        });
+
+        msg!("Player {} unlocked achievement #{} for {} tokens", player, achievement.id, amount);
+        Ok(())
+    }
+
+    // Referral bonus settings (pools authority only); caps of 0 are unlimited
+    pub fn set_referral_config(
+        ctx: Context<ManageGamePools>,
+        referral_bonus_bps: u16,
+        referral_pay_on_claim: bool,
+        max_referral_bonus_per_referee: u64,
+        max_referral_bonus_per_referrer: u64,
+    ) -> Result<()> {
+        require!(
+            referral_bonus_bps as u64 <= BPS_DENOMINATOR,
+            GameTokenError::InvalidReferralConfig
+        );
+
+        let game_pools = &mut ctx.accounts.game_pools;
+        game_pools.referral_bonus_bps = referral_bonus_bps;
+        game_pools.referral_pay_on_claim = referral_pay_on_claim;
+        game_pools.max_referral_bonus_per_referee = max_referral_bonus_per_referee;
+        game_pools.max_referral_bonus_per_referrer = max_referral_bonus_per_referrer;
+
+        emit!(ReferralConfigUpdatedEvent {
+            referral_bonus_bps,
+            referral_pay_on_claim,
+            max_referral_bonus_per_referee,
+            max_referral_bonus_per_referrer,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Referral bonus set to {} bps", referral_bonus_bps);
+        Ok(())
+    }
+
+    // Register the player's referrer; can only be done once per player
+    pub fn register_referrer(ctx: Context<RegisterReferrer>, referrer: Pubkey) -> Result<()> {
+        let player = ctx.accounts.player.key();
+        require!(
+            referrer != Pubkey::default() && referrer != player,
+            GameTokenError::InvalidReferrer
+        );
+
+        let player_stats = &mut ctx.accounts.player_stats;
+        if player_stats.player == Pubkey::default() {
+            player_stats.player = player;
+            player_stats.bump = ctx.bumps.player_stats;
+        }
+        require!(player_stats.referrer == Pubkey::default(), GameTokenError::InvalidReferrer);
+        player_stats.referrer = referrer;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let referral = &mut ctx.accounts.referral;
+        referral.player = player;
+        referral.referrer = referrer;
+        referral.registered_at = current_time;
+        referral.bonus_paid = 0;
+        referral.bump = ctx.bumps.referral;
+
+        let referrer_stats = &mut ctx.accounts.referrer_stats;
+        if referrer_stats.referrer == Pubkey::default() {
+            referrer_stats.referrer = referrer;
+            referrer_stats.bump = ctx.bumps.referrer_stats;
+        }
+        referrer_stats.referee_count = referrer_stats
+            .referee_count
+            .checked_add(1)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(ReferrerRegisteredEvent {
+            player,
+            referrer,
+            referee_count: referrer_stats.referee_count,
+            timestamp: current_time,
+        });
+
+        msg!("Player {} registered referrer {}", player, referrer);
+        Ok(())
+    }
+
+    // Open the next season (pools authority only); the previous one must be closed
+    // reward_budget caps what players can earn during the season, 0 is unlimited
+    pub fn open_season(
+        ctx: Context<OpenSeason>,
+        season_id: u32,
+        start_time: i64,
+        end_time: i64,
+        reward_budget: u64,
+    ) -> Result<()> {
+        let game_pools = &mut ctx.accounts.game_pools;
+        require!(!game_pools.season_active, GameTokenError::SeasonStillOpen);
+        require!(
+            season_id == game_pools.current_season_id + 1 && start_time < end_time,
+            GameTokenError::InvalidSeason
+        );
+
+        let season = &mut ctx.accounts.season;
+        season.id = season_id;
+        season.start_time = start_time;
+        season.end_time = end_time;
+        season.reward_budget = reward_budget;
+        season.total_earned = 0;
+        season.participant_count = 0;
+        season.is_closed = false;
+        season.closed_at = 0;
+        season.bump = ctx.bumps.season;
+        season.leaderboard_enabled = false;
+
+        game_pools.current_season_id = season_id;
+        game_pools.season_active = true;
+
+        emit!(SeasonOpenedEvent {
+            season_id,
+            start_time,
+            end_time,
+            reward_budget,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Opened season #{} ({} - {})", season_id, start_time, end_time);
+        Ok(())
+    }
+
+    // Freeze the current season's stats so the next season can be opened
+    // (pools authority only)
+    pub fn close_season(ctx: Context<CloseSeason>) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        let season = &mut ctx.accounts.season;
+        require!(!season.is_closed, GameTokenError::SeasonClosed);
+        season.is_closed = true;
+        season.closed_at = current_time;
+        ctx.accounts.game_pools.season_active = false;
+
+        emit!(SeasonClosedEvent {
+            season_id: season.id,
+            total_earned: season.total_earned,
+            participant_count: season.participant_count,
+            timestamp: current_time,
+        });
+
+        msg!("Closed season #{} with {} tokens earned", season.id, season.total_earned);
+        Ok(())
+    }
+
+    // Attach a top-N leaderboard and its prize table to the open season
+    // (pools authority only); prize_table[i] is paid to rank i
+    pub fn initialize_leaderboard(
+        ctx: Context<InitializeLeaderboard>,
+        prize_table: Vec<u64>,
+    ) -> Result<()> {
+        require!(prize_table.len() <= LEADERBOARD_SIZE, GameTokenError::InvalidPrizeTable);
+        let season = &mut ctx.accounts.season;
+        require!(!season.is_closed, GameTokenError::SeasonClosed);
+        season.leaderboard_enabled = true;
+
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.season_id = season.id;
+        leaderboard.entries = Vec::new();
+        leaderboard.prize_table = prize_table.clone();
+        leaderboard.prizes_distributed = false;
+        leaderboard.bump = ctx.bumps.leaderboard;
+
+        emit!(LeaderboardPrizeTableUpdatedEvent {
+            season_id: season.id,
+            prize_table,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Initialized leaderboard for season #{}", season.id);
+        Ok(())
+    }
+
+    // Replace a leaderboard's prize table before prizes are paid (pools authority only)
+    pub fn set_leaderboard_prizes(
+        ctx: Context<SetLeaderboardPrizes>,
+        prize_table: Vec<u64>,
+    ) -> Result<()> {
+        require!(prize_table.len() <= LEADERBOARD_SIZE, GameTokenError::InvalidPrizeTable);
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        require!(!leaderboard.prizes_distributed, GameTokenError::PrizesAlreadyDistributed);
+        leaderboard.prize_table = prize_table.clone();
+
+        emit!(LeaderboardPrizeTableUpdatedEvent {
+            season_id: leaderboard.season_id,
+            prize_table,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Updated prize table for season #{}", leaderboard.season_id);
+        Ok(())
+    }
+
+    // Pay the prize table from the reward pool once the season is closed (permissionless)
+    // The winners' token accounts go in remaining_accounts, in rank order
+    pub fn distribute_leaderboard_prizes<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DistributeLeaderboardPrizes<'info>>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.authority.is_paused(PAUSE_CLAIM), GameTokenError::OperationPaused);
+        require!(ctx.accounts.season.is_closed, GameTokenError::SeasonStillOpen);
+        let leaderboard = &ctx.accounts.leaderboard;
+        require!(!leaderboard.prizes_distributed, GameTokenError::PrizesAlreadyDistributed);
+
+        let winners: Vec<LeaderboardEntry> = leaderboard
+            .entries
+            .iter()
+            .take(leaderboard.prize_table.len())
+            .cloned()
+            .collect();
+        let prizes: Vec<u64> = leaderboard.prize_table[..winners.len()].to_vec();
+        require!(
+            ctx.remaining_accounts.len() == winners.len(),
+            GameTokenError::LeaderboardWinnerMismatch
+        );
+        let total: u64 = prizes
+            .iter()
+            .try_fold(0u64, |total, prize| total.checked_add(*prize))
+            .ok_or(GameTokenError::MathOverflow)?;
+        require!(ctx.accounts.game_pools.reward_pool >= total, GameTokenError::InsufficientPool);
+
+        let game_pools_bump = ctx.accounts.game_pools.bump;
+        let mut total_fees: u64 = 0;
+        for ((winner, &prize), account) in winners
+            .iter()
+            .zip(prizes.iter())
+            .zip(ctx.remaining_accounts.iter())
+        {
+            let winner_token_account = InterfaceAccount::<TokenAccount>::try_from(account)?;
+            require!(
+                winner_token_account.owner == winner.player
+                    && winner_token_account.mint == ctx.accounts.game_pools.game_token_mint,
+                GameTokenError::LeaderboardWinnerMismatch
+            );
+            if prize == 0 {
+                continue;
+            }
+            total_fees = total_fees
+                .checked_add(transfer_fee_for(&ctx.accounts.game_token_mint, prize)?)
+                .ok_or(GameTokenError::MathOverflow)?;
+
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.reward_pool_token_account.to_account_info(),
+                        to: account.clone(),
+                        authority: ctx.accounts.game_pools.to_account_info(),
+                        mint: ctx.accounts.game_token_mint.to_account_info(),
+                    },
+                    &[&[
+                        b"game_pools",
+                        &[game_pools_bump]
+                    ]]
+                ),
+                prize,
+                ctx.accounts.game_token_mint.decimals,
+            )?;
+        }
+
+        ctx.accounts.game_pools.reward_pool -= total;
+        let leaderboard = &mut ctx.accounts.leaderboard;
+        leaderboard.prizes_distributed = true;
+
+        emit!(LeaderboardPrizesDistributedEvent {
+            season_id: leaderboard.season_id,
+            winners: winners.iter().map(|winner| winner.player).collect(),
+            prizes,
+            total,
+            total_fees,
+            remaining_reward_pool: ctx.accounts.game_pools.reward_pool,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Distributed {} tokens in prizes for season #{}", total, leaderboard.season_id);
+        Ok(())
+    }
+
+    // Streak reward table and streak freeze pricing (pools authority only)
+    // reward_table[n - 1] is paid on day n of a streak; a freeze_price of 0 disables freezes
+    pub fn set_streak_config(
+        ctx: Context<SetStreakConfig>,
+        reward_table: Vec<u64>,
+        freeze_price: u64,
+        max_freezes: u8,
+    ) -> Result<()> {
+        require!(
+            !reward_table.is_empty() && reward_table.len() <= MAX_STREAK_TABLE,
+            GameTokenError::InvalidStreakConfig
+        );
+
+        let streak_config = &mut ctx.accounts.streak_config;
+        streak_config.reward_table = reward_table.clone();
+        streak_config.freeze_price = freeze_price;
+        streak_config.max_freezes = max_freezes;
+        streak_config.bump = ctx.bumps.streak_config;
+
+        emit!(StreakConfigUpdatedEvent {
+            reward_table,
+            freeze_price,
+            max_freezes,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Streak config set: freeze price {}, max freezes {}", freeze_price, max_freezes);
+        Ok(())
+    }
+
+    // Daily check-in, once per UTC day. Consecutive days grow the streak; a missed
+    // day resets it unless enough streak freezes are held to cover the gap
+    pub fn check_in(ctx: Context<CheckIn>) -> Result<()> {
+        require!(!ctx.accounts.authority.is_paused(PAUSE_EARN), GameTokenError::OperationPaused);
+
+        let game_pools_bump = ctx.accounts.game_pools.bump;
+        let current_time = Clock::get()?.unix_timestamp;
+        let today = current_time / SECONDS_PER_DAY;
+
+        let player_stats = &mut ctx.accounts.player_stats;
+        if player_stats.player == Pubkey::default() {
+            player_stats.player = ctx.accounts.player.key();
+            player_stats.bump = ctx.bumps.player_stats;
+        }
+        let freezes_used = player_stats.record_check_in(today)?;
+
+        // Escalating reward, paid as far as the reward pool allows
+        let reward = ctx
+            .accounts
+            .streak_config
+            .reward_for(player_stats.current_streak)
+            .min(ctx.accounts.game_pools.reward_pool);
+        let fee = transfer_fee_for(&ctx.accounts.game_token_mint, reward)?;
+        if reward > 0 {
+            anchor_spl::token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    TransferChecked {
+                        from: ctx.accounts.reward_pool_token_account.to_account_info(),
+                        to: ctx.accounts.player_token_account.to_account_info(),
+                        authority: ctx.accounts.game_pools.to_account_info(),
+                        mint: ctx.accounts.game_token_mint.to_account_info(),
+                    },
+                    &[&[
+                        b"game_pools",
+                        &[game_pools_bump]
+                    ]]
+                ),
+                reward,
+                ctx.accounts.game_token_mint.decimals,
+            )?;
+            ctx.accounts.game_pools.reward_pool -= reward;
+            player_stats.total_transfer_fees = player_stats
+                .total_transfer_fees
+                .checked_add(fee)
+                .ok_or(GameTokenError::MathOverflow)?;
+        }
+
+        emit!(CheckInEvent {
+            player: player_stats.player,
+            day: today,
+            current_streak: player_stats.current_streak,
+            longest_streak: player_stats.longest_streak,
+            freezes_used,
+            reward,
+            fee,
+            timestamp: current_time,
+        });
+
+        msg!(
+            "Player {} checked in, streak {} day(s), reward {}",
+            player_stats.player,
+            player_stats.current_streak,
+            reward
+        );
+        Ok(())
+    }
+
+    // Buy streak freezes with game tokens; the payment goes to the reward pool
+    pub fn buy_streak_freeze(ctx: Context<BuyStreakFreeze>, quantity: u8) -> Result<()> {
+        require!(!ctx.accounts.authority.is_paused(PAUSE_EARN), GameTokenError::OperationPaused);
+
+        let streak_config = &ctx.accounts.streak_config;
+        require!(streak_config.freeze_price > 0, GameTokenError::StreakFreezeUnavailable);
+        require!(quantity > 0, GameTokenError::InvalidStreakConfig);
+
+        let player_stats = &ctx.accounts.player_stats;
+        let freezes = player_stats
+            .streak_freezes
+            .checked_add(quantity)
+            .ok_or(GameTokenError::MathOverflow)?;
+        require!(freezes <= streak_config.max_freezes, GameTokenError::TooManyStreakFreezes);
+
+        let price = streak_config
+            .freeze_price
+            .checked_mul(quantity as u64)
+            .ok_or(GameTokenError::MathOverflow)?;
+        let fee = transfer_fee_for(&ctx.accounts.game_token_mint, price)?;
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.player_token_account.to_account_info(),
+                    to: ctx.accounts.reward_pool_token_account.to_account_info(),
+                    authority: ctx.accounts.player.to_account_info(),
+                    mint: ctx.accounts.game_token_mint.to_account_info(),
+                },
+            ),
+            price,
+            ctx.accounts.game_token_mint.decimals,
+        )?;
+
+        let game_pools = &mut ctx.accounts.game_pools;
+        game_pools.reward_pool = game_pools
+            .reward_pool
+            .checked_add(price - fee)
+            .ok_or(GameTokenError::MathOverflow)?;
+        let player_stats = &mut ctx.accounts.player_stats;
+        player_stats.streak_freezes = freezes;
+
+        emit!(StreakFreezePurchasedEvent {
+            player: player_stats.player,
+            quantity,
+            price,
+            streak_freezes: freezes,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Player {} bought {} streak freeze(s)", player_stats.player, quantity);
+        Ok(())
+    }
+
+    // Bring a MintingAuthority, GameTokenPools or PlayerMintStats account created by an
+    // earlier program version up to the current layout (permissionless, payer funds
+    // the extra rent). After an upgrade, run this once per existing account before
+    // using it, since older accounts no longer deserialize
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        let account = ctx.accounts.account.to_account_info();
+        let old_len = account.data_len();
+        let upgraded = upgrade_legacy_account_data(&account.try_borrow_data()?)?;
+
+        let top_up = Rent::get()?
+            .minimum_balance(upgraded.len())
+            .saturating_sub(account.lamports());
+        if top_up > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: account.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        account.resize(upgraded.len())?;
+        account.try_borrow_mut_data()?.copy_from_slice(&upgraded);
+
+        emit!(AccountMigratedEvent {
+            account: account.key(),
+            old_len: old_len as u64,
+            new_len: upgraded.len() as u64,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        msg!("Migrated account {:?}: {} -> {} bytes", account.key(), old_len, upgraded.len());
+        Ok(())
+    }
+}
+
+// Voucher helpers
+pub fn earn_voucher_message(player: &Pubkey, amount: u64, nonce: u64, expiry: i64) -> Vec<u8> {
+    let mut message = Vec::with_capacity(EARN_VOUCHER_MESSAGE_LEN);
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(player.as_ref());
+    message.extend_from_slice(&amount.to_le_bytes());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+pub fn achievement_voucher_message(player: &Pubkey, achievement_id: u16) -> Vec<u8> {
+    let mut message = Vec::with_capacity(ACHIEVEMENT_VOUCHER_MESSAGE_LEN);
+    message.extend_from_slice(crate::ID.as_ref());
+    message.extend_from_slice(player.as_ref());
+    message.extend_from_slice(&achievement_id.to_le_bytes());
+    message
+}
+
+// Checks that the instruction preceding the current one is an ed25519 program
+// instruction carrying exactly one signature by `signer` over `message`
+pub fn verify_ed25519_instruction(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = instructions_sysvar::load_current_index_checked(instructions)?;
+    require!(current_index > 0, GameTokenError::InvalidVoucherSignature);
+    let ix = instructions_sysvar::load_instruction_at_checked(
+        (current_index - 1) as usize,
+        instructions,
+    )?;
+    require!(ix.program_id == ed25519_program::ID, GameTokenError::InvalidVoucherSignature);
+    require!(ix.accounts.is_empty(), GameTokenError::InvalidVoucherSignature);
+
+    // Layout: [num_signatures: u8, padding: u8, offsets: 7 x u16, ...payload]
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, GameTokenError::InvalidVoucherSignature);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let signature_ix_index = read_u16(4);
+    let public_key_offset = read_u16(6) as usize;
+    let public_key_ix_index = read_u16(8);
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let message_ix_index = read_u16(14);
+
+    // All data must live inside the ed25519 instruction itself
+    require!(
+        signature_ix_index == u16::MAX
+            && public_key_ix_index == u16::MAX
+            && message_ix_index == u16::MAX,
+        GameTokenError::InvalidVoucherSignature
+    );
+
+    let signed_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(GameTokenError::InvalidVoucherSignature)?;
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(GameTokenError::InvalidVoucherSignature)?;
+    require!(signed_key == signer.as_ref(), GameTokenError::InvalidVoucherSignature);
+    require!(signed_message == message, GameTokenError::InvalidVoucherSignature);
+
+    Ok(())
+}
+
+// Multisig helpers
+pub fn validate_multisig_owners(owners: &[Pubkey], threshold: u8) -> Result<()> {
+    require!(
+        !owners.is_empty() && owners.len() <= MAX_MULTISIG_OWNERS,
+        GameTokenError::InvalidMultisigOwners
+    );
+    for (i, owner) in owners.iter().enumerate() {
+        require!(!owners[..i].contains(owner), GameTokenError::InvalidMultisigOwners);
+    }
+    require!(
+        threshold > 0 && threshold as usize <= owners.len(),
+        GameTokenError::InvalidThreshold
+    );
+    Ok(())
+}
+
+// Split helpers
+pub fn validate_split(recipients: &[SplitRecipient], dust_recipient: u8) -> Result<()> {
+    require!(
+        !recipients.is_empty() && recipients.len() <= MAX_SPLIT_RECIPIENTS,
+        GameTokenError::InvalidSplitConfig
+    );
+    require!(
+        (dust_recipient as usize) < recipients.len(),
+        GameTokenError::InvalidSplitConfig
+    );
+
+    let mut total_bps: u64 = 0;
+    for (i, recipient) in recipients.iter().enumerate() {
+        require!(recipient.weight_bps > 0, GameTokenError::InvalidSplitConfig);
+        require!(
+            !recipients[..i]
+                .iter()
+                .any(|other| other.token_account == recipient.token_account),
+            GameTokenError::InvalidSplitConfig
+        );
+        total_bps += recipient.weight_bps as u64;
+    }
+    require!(total_bps == BPS_DENOMINATOR, GameTokenError::InvalidSplitConfig);
+    Ok(())
+}
+
+// Mints each share to the matching split recipient account, in config order
+pub fn mint_to_split_recipients<'info>(
+    token_program: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    minting_authority: AccountInfo<'info>,
+    authority: &MintingAuthority,
+    split_config: &SplitConfig,
+    shares: &[u64],
+    recipient_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    require!(
+        recipient_accounts.len() == split_config.recipients.len(),
+        GameTokenError::SplitRecipientMismatch
+    );
+
+    for ((recipient, &share), recipient_account) in split_config
+        .recipients
+        .iter()
+        .zip(shares.iter())
+        .zip(recipient_accounts.iter())
+    {
+        require_keys_eq!(
+            recipient_account.key(),
+            recipient.token_account,
+            GameTokenError::SplitRecipientMismatch
+        );
+        check_split_recipient_account(authority, mint.key, token_program.key, recipient_account)?;
+        if share == 0 {
+            continue;
+        }
+
+        anchor_spl::token_interface::mint_to(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                MintTo {
+                    mint: mint.clone(),
+                    to: recipient_account.clone(),
+                    authority: minting_authority.clone(),
+                },
+                &[&[
+                    b"minting_authority",
+                    &[authority.bump]
+                ]]
+            ),
+            share,
+        )?;
+    }
+    Ok(())
+}
+
+// Checks the token accounts of a new split config's recipients, passed in config order
+pub fn check_split_recipients(
+    authority: &MintingAuthority,
+    mint: &AccountInfo,
+    recipients: &[SplitRecipient],
+    recipient_accounts: &[AccountInfo],
+) -> Result<()> {
+    require!(
+        recipient_accounts.len() == recipients.len(),
+        GameTokenError::SplitRecipientMismatch
+    );
+    for (recipient, recipient_account) in recipients.iter().zip(recipient_accounts.iter()) {
+        require_keys_eq!(
+            recipient_account.key(),
+            recipient.token_account,
+            GameTokenError::SplitRecipientMismatch
+        );
+        check_split_recipient_account(authority, mint.key, mint.owner, recipient_account)?;
+    }
+    Ok(())
+}
+
+// A split recipient account must hold the game mint. One held by the owner must be
+// the owner's associated token account, and is not allowed at all once owner vesting
+// is set up, so the owner share can only reach the owner through the escrow
+pub fn check_split_recipient_account(
+    authority: &MintingAuthority,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    recipient_account: &AccountInfo,
+) -> Result<()> {
+    require_keys_eq!(*recipient_account.owner, *token_program, GameTokenError::SplitRecipientMismatch);
+    let token_account = TokenAccount::try_deserialize(&mut &recipient_account.try_borrow_data()?[..])?;
+    require_keys_eq!(token_account.mint, *mint, GameTokenError::SplitRecipientMismatch);
+    if token_account.owner == authority.owner {
+        require!(
+            authority.owner_vesting_token_account == Pubkey::default(),
+            GameTokenError::OwnerShareMustVest
+        );
+        require_keys_eq!(
+            recipient_account.key(),
+            get_associated_token_address_with_program_id(&authority.owner, mint, token_program),
+            GameTokenError::InvalidOwnerShareAccount
+        );
+    }
+    Ok(())
+}
+
+// Staking helpers
+pub fn withdraw_unstaked_tokens(accounts: &mut Unstake, current_time: i64) -> Result<()> {
+    let player_stake = &mut accounts.player_stake;
+    require!(player_stake.unstaking_amount > 0, GameTokenError::NothingToWithdraw);
+    let amount = player_stake.withdrawable(current_time);
+    require!(amount > 0, GameTokenError::UnstakeCooldownActive);
+    let fee = transfer_fee_for(&accounts.game_token_mint, amount)?;
+
+    anchor_spl::token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: accounts.stake_vault.to_account_info(),
+                to: accounts.player_token_account.to_account_info(),
+                authority: accounts.stake_pool.to_account_info(),
+                mint: accounts.game_token_mint.to_account_info(),
+            },
+            &[&[
+                b"stake_pool",
+                &[accounts.stake_pool.bump]
+            ]]
+        ),
+        amount,
+        accounts.game_token_mint.decimals,
+    )?;
+
+    player_stake.release_withdrawable(current_time);
+
+    emit!(UnstakeWithdrawnEvent {
+        player: player_stake.player,
+        amount,
+        fee,
+        timestamp: current_time,
+    });
+
+    msg!("Player {} withdrew {} unstaked tokens", player_stake.player, amount);
+    Ok(())
+}
+
+// Records the owner vesting escrow's split share, if any, as a new vesting deposit
+pub fn record_owner_vesting_deposit(
+    authority: &MintingAuthority,
+    owner_vesting: Option<&mut VestingEscrow>,
+    split_config: &SplitConfig,
+    shares: &[u64],
+    current_time: i64,
+) -> Result<u64> {
+    if authority.owner_vesting_token_account == Pubkey::default() {
+        return Ok(0);
+    }
+    let vested_amount = split_config.share_of(shares, &authority.owner_vesting_token_account);
+    if vested_amount == 0 {
+        return Ok(0);
+    }
+
+    let vesting = owner_vesting.ok_or(GameTokenError::VestingAccountRequired)?;
+    vesting.deposit(vested_amount, current_time)?;
+    Ok(vested_amount)
+}
+
+// Owner share minted straight to token accounts held by the owner, i.e. released
+// without vesting. Recipient accounts are in split config order, as for minting
+pub fn owner_released_share(
+    owner: &Pubkey,
+    shares: &[u64],
+    recipient_accounts: &[AccountInfo],
+) -> Result<u64> {
+    let mut released: u64 = 0;
+    for (&share, recipient_account) in shares.iter().zip(recipient_accounts.iter()) {
+        if share == 0 {
+            continue;
+        }
+        let token_account = TokenAccount::try_deserialize(&mut &recipient_account.try_borrow_data()?[..])?;
+        if token_account.owner == *owner {
+            released = released.checked_add(share).ok_or(GameTokenError::MathOverflow)?;
+        }
+    }
+    Ok(released)
+}
+
+// Fee withheld by Token-2022 when transferring `amount` in the current epoch;
+// zero for classic SPL mints and mints without the transfer-fee extension
+pub fn transfer_fee_for(mint: &InterfaceAccount<Mint>, amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != anchor_spl::token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    match state.get_extension::<TransferFeeConfig>() {
+        Ok(config) => Ok(config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(GameTokenError::MathOverflow)?),
+        Err(_) => Ok(0),
+    }
+}
+
+// Converts a UI amount (whole tokens) to base units using the mint's decimals;
+// base-unit amounts pass through unchanged
+pub fn to_base_units(amount: u64, ui_amount: bool, decimals: u8) -> Result<u64> {
+    if !ui_amount {
+        return Ok(amount);
+    }
+    10u64
+        .checked_pow(decimals as u32)
+        .and_then(|scale| amount.checked_mul(scale))
+        .ok_or(GameTokenError::MathOverflow.into())
+}
+
+// Accounts for create_game_mint
+pub struct GameMintAccounts<'info> {
+    pub authority: AccountInfo<'info>,
+    pub authority_bump: u8,
+    pub game_token_mint: AccountInfo<'info>,
+    pub mint_bump: u8,
+    pub owner: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+// Creates the game mint at the game_token_mint PDA, paid for by the owner. The mint
+// authority is the minting_authority PDA. With the Token-2022 program and metadata,
+// the mint carries a metadata pointer to itself plus token metadata
+pub fn create_game_mint(
+    accounts: GameMintAccounts,
+    decimals: u8,
+    freeze_authority: Option<Pubkey>,
+    metadata: Option<GameMintMetadata>,
+) -> Result<()> {
+    let is_token_2022 = *accounts.token_program.key == anchor_spl::token_2022::ID;
+    let mint_key = *accounts.game_token_mint.key;
+    let mint_bump = accounts.mint_bump;
+    let authority_bump = accounts.authority_bump;
+
+    // Metadata extensions only exist on Token-2022
+    let (extensions, metadata_len) = match &metadata {
+        Some(metadata) => {
+            require!(is_token_2022, GameTokenError::MetadataRequiresToken2022);
+            require!(
+                !metadata.name.is_empty()
+                    && metadata.name.len() <= MAX_TOKEN_NAME_LEN
+                    && !metadata.symbol.is_empty()
+                    && metadata.symbol.len() <= MAX_TOKEN_SYMBOL_LEN
+                    && metadata.uri.len() <= MAX_TOKEN_URI_LEN,
+                GameTokenError::InvalidMintMetadata
+            );
+            let token_metadata = TokenMetadata {
+                mint: mint_key,
+                name: metadata.name.clone(),
+                symbol: metadata.symbol.clone(),
+                uri: metadata.uri.clone(),
+                ..Default::default()
+            };
+            (vec![ExtensionType::MetadataPointer], token_metadata.tlv_size_of()?)
+        }
+        None => (vec![], 0),
+    };
+
+    // Fund the metadata up front; the token program reallocs the mint when writing it
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&extensions)?;
+    create_pda_account(
+        &accounts.owner,
+        &accounts.game_token_mint,
+        &accounts.system_program,
+        space,
+        accounts.token_program.key,
+        &[b"game_token_mint", &[mint_bump]],
+    )?;
+    // Lamports for the metadata the token program writes later
+    let top_up = Rent::get()?
+        .minimum_balance(space + metadata_len)
+        .saturating_sub(accounts.game_token_mint.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                accounts.system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: accounts.owner.clone(),
+                    to: accounts.game_token_mint.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+
+    if metadata.is_some() {
+        anchor_spl::token_2022_extensions::metadata_pointer_initialize(
+            CpiContext::new(
+                accounts.token_program.clone(),
+                MetadataPointerInitialize {
+                    token_program_id: accounts.token_program.clone(),
+                    mint: accounts.game_token_mint.clone(),
+                },
+            ),
+            Some(*accounts.owner.key),
+            Some(mint_key),
+        )?;
+    }
+
+    anchor_spl::token_2022::initialize_mint2(
+        CpiContext::new(
+            accounts.token_program.clone(),
+            anchor_spl::token_2022::InitializeMint2 {
+                mint: accounts.game_token_mint.clone(),
+            },
+        ),
+        decimals,
+        accounts.authority.key,
+        freeze_authority.as_ref(),
+    )?;
+
+    if let Some(metadata) = metadata {
+        anchor_spl::token_2022_extensions::token_metadata_initialize(
+            CpiContext::new_with_signer(
+                accounts.token_program.clone(),
+                TokenMetadataInitialize {
+                    program_id: accounts.token_program.clone(),
+                    metadata: accounts.game_token_mint.clone(),
+                    update_authority: accounts.owner.clone(),
+                    mint_authority: accounts.authority.clone(),
+                    mint: accounts.game_token_mint.clone(),
+                },
+                &[&[
+                    b"minting_authority",
+                    &[authority_bump]
+                ]]
+            ),
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+        )?;
+    }
+
+    emit!(GameMintInitializedEvent {
+        mint: mint_key,
+        token_program: *accounts.token_program.key,
+        decimals,
+        freeze_authority,
+        has_metadata: !extensions.is_empty(),
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Initialized game mint {:?} with {} decimals", mint_key, decimals);
+    Ok(())
+}
+
+// Creates a PDA account of `space` bytes owned by `owner`, funded by `payer`. Unlike a
+// plain create_account this also works when the address was already sent lamports
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let lamports = Rent::get()?.minimum_balance(space);
+
+    if account.lamports() == 0 {
+        return anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space as u64,
+            owner,
+        );
+    }
+
+    let top_up = lamports.saturating_sub(account.lamports());
+    if top_up > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    anchor_lang::system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    anchor_lang::system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Assign {
+                account_to_assign: account.clone(),
+            },
+            &[seeds],
+        ),
+        owner,
+    )
+}
+
+// Migration helpers
+// Rewrites the data of an account created by an earlier program version in the current
+// layout. Fields are only ever appended, so the old bytes keep their meaning; appended
+// fields start zeroed, except those initialized to a non-zero default
+pub fn upgrade_legacy_account_data(data: &[u8]) -> Result<Vec<u8>> {
+    let discriminator = data.get(..8).ok_or(GameTokenError::InvalidMigrationAccount)?;
+    let space = if discriminator == MintingAuthority::DISCRIMINATOR {
+        8 + MintingAuthority::INIT_SPACE
+    } else if discriminator == GameTokenPools::DISCRIMINATOR {
+        8 + GameTokenPools::INIT_SPACE
+    } else if discriminator == PlayerMintStats::DISCRIMINATOR {
+        8 + PlayerMintStats::INIT_SPACE
+    } else {
+        return err!(GameTokenError::InvalidMigrationAccount);
+    };
+    require!(data.len() < space, GameTokenError::AccountAlreadyMigrated);
+
+    let mut upgraded = data.to_vec();
+    upgraded.resize(space, 0);
+    if discriminator == MintingAuthority::DISCRIMINATOR {
+        let mut authority = MintingAuthority::try_deserialize(&mut &upgraded[..])?;
+        authority.emission_decay_bps = BPS_DENOMINATOR as u16;
+        authority.emission_decay_interval = 1;
+        authority.try_serialize(&mut &mut upgraded[..])?;
+    } else if discriminator == GameTokenPools::DISCRIMINATOR {
+        let mut game_pools = GameTokenPools::try_deserialize(&mut &upgraded[..])?;
+        game_pools.earn_epoch_length = 86_400;
+        game_pools.try_serialize(&mut &mut upgraded[..])?;
+    }
+    Ok(upgraded)
+}
+
+// Account Structures
+#[account]
+#[derive(Default, InitSpace)]
+pub struct MintingAuthority {
+    pub owner: Pubkey,
+    pub total_minted: u64,
+    pub is_infinite: bool,
+    pub max_supply: u64,
+    pub max_mints_per_player_per_minute: u8,
+    pub bump: u8,
+    #[max_len(MAX_MINTERS)]
+    pub minters: Vec<MinterQuota>,
+    pub max_tokens_per_player_per_hour: u64,
+    pub max_tokens_per_player_per_day: u64,
+    pub pause_flags: u8,
+    pub pause_reason: u16,
+    pub paused_at: i64,
+    pub pending_owner: Pubkey,
+    pub timelock_delay: i64,
+    pub timelock_count: u64,
+    // Emission schedule: epoch `e` emits initial * (decay_bps / 10_000) ^ (e / decay_interval)
+    pub emission_epoch_length: i64,
+    pub emission_initial_per_epoch: u64,
+    pub emission_decay_bps: u16,
+    pub emission_decay_interval: u32,
+    pub emission_start: i64,
+    // Time up to which emission has been minted by crank_emission
+    pub last_mint_timestamp: i64,
+    // Split recipient whose share is vested instead of released, see VestingEscrow
+    pub owner_vesting_token_account: Pubkey,
+    // Which supply max_supply caps; once locked, the cap can only be lowered
+    pub supply_cap_mode: SupplyCapMode,
+    pub max_supply_locked: bool,
+    // Burns observed by reconcile_supply, so total_minted - total_burned tracks circulating supply
+    pub total_burned: u64,
+    pub last_reconciled_at: i64,
+}
+
+impl MintingAuthority {
+    pub fn is_paused(&self, operation: u8) -> bool {
+        self.pause_flags & (operation | PAUSE_GLOBAL) != 0
+    }
+
+    pub fn emission_enabled(&self) -> bool {
+        self.emission_epoch_length > 0 && self.emission_initial_per_epoch > 0
+    }
+
+    pub fn emission_epoch_at(&self, timestamp: i64) -> u64 {
+        if self.emission_epoch_length <= 0 || timestamp <= self.emission_start {
+            return 0;
+        }
+        ((timestamp - self.emission_start) / self.emission_epoch_length) as u64
+    }
+
+    // initial * (decay_bps / 10_000) ^ steps, with the power taken in 1e18 fixed point
+    // by squaring so the cost stays logarithmic in the schedule's age
+    pub fn emission_for_epoch(&self, epoch: u64) -> u64 {
+        let mut decay_steps = epoch / self.emission_decay_interval.max(1) as u64;
+        if decay_steps == 0 || self.emission_decay_bps as u64 == BPS_DENOMINATOR {
+            return self.emission_initial_per_epoch;
+        }
+
+        let mut factor = EMISSION_DECAY_SCALE;
+        let mut base = self.emission_decay_bps as u128 * EMISSION_DECAY_SCALE / BPS_DENOMINATOR as u128;
+        while decay_steps > 0 && factor > 0 {
+            if decay_steps & 1 == 1 {
+                factor = factor * base / EMISSION_DECAY_SCALE;
+            }
+            base = base * base / EMISSION_DECAY_SCALE;
+            decay_steps >>= 1;
+        }
+        (self.emission_initial_per_epoch as u128 * factor / EMISSION_DECAY_SCALE) as u64
+    }
+
+    // Room left under the supply cap, measured against both the program's mint
+    // counter and the mint's actual supply (tokens minted before hand-over count too)
+    pub fn remaining_supply(&self, mint_supply: u64) -> u64 {
+        if self.is_infinite {
+            return u64::MAX;
+        }
+        match self.supply_cap_mode {
+            SupplyCapMode::TotalMinted => {
+                self.max_supply.saturating_sub(self.total_minted.max(mint_supply))
+            }
+            SupplyCapMode::Circulating => self.max_supply.saturating_sub(mint_supply),
+        }
+    }
+
+    // A locked cap may only be lowered: no infinite supply, no higher max_supply and
+    // no switch to the (more permissive) circulating mode
+    pub fn check_supply_cap_change(&self, change: &ParameterChange) -> Result<()> {
+        if let ParameterChange::SupplyCap { is_infinite, max_supply, cap_mode } = *change {
+            if self.max_supply_locked {
+                require!(
+                    !is_infinite
+                        && max_supply <= self.max_supply
+                        && (cap_mode == self.supply_cap_mode
+                            || cap_mode == SupplyCapMode::TotalMinted),
+                    GameTokenError::SupplyCapLocked
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // Tokens due from last_mint_timestamp to `current_time`, emitted linearly within
+    // each epoch. Walks at most MAX_CRANK_EPOCHS epochs and returns the amount together
+    // with the timestamp it settles up to
+    pub fn emission_due(&self, current_time: i64) -> Result<(u64, i64)> {
+        let length = self.emission_epoch_length;
+        let mut cursor = self.last_mint_timestamp.max(self.emission_start);
+        let mut due: u64 = 0;
+
+        for _ in 0..MAX_CRANK_EPOCHS {
+            if cursor >= current_time {
+                break;
+            }
+            let epoch = self.emission_epoch_at(cursor);
+            let epoch_start = self.emission_start + epoch as i64 * length;
+            let segment_end = (epoch_start + length).min(current_time);
+
+            // Difference of cumulative amounts, so splitting an epoch across cranks loses no dust
+            let per_epoch = self.emission_for_epoch(epoch) as u128;
+            let emitted_at = |t: i64| per_epoch * (t - epoch_start) as u128 / length as u128;
+            let segment = (emitted_at(segment_end) - emitted_at(cursor)) as u64;
+
+            due = due.checked_add(segment).ok_or(GameTokenError::MathOverflow)?;
+            cursor = segment_end;
+        }
+
+        Ok((due, cursor))
+    }
+
+    pub fn apply_parameter_change(&mut self, change: &ParameterChange, current_time: i64) -> Result<()> {
+        self.check_supply_cap_change(change)?;
+        match *change {
+            ParameterChange::SupplyCap { is_infinite, max_supply, cap_mode } => {
+                // Circulating caps are checked against the mint supply by the caller
+                require!(
+                    is_infinite
+                        || cap_mode == SupplyCapMode::Circulating
+                        || max_supply >= self.total_minted,
+                    GameTokenError::InvalidParameterChange
+                );
+                self.is_infinite = is_infinite;
+                self.max_supply = max_supply;
+                self.supply_cap_mode = cap_mode;
+            }
+            ParameterChange::RateLimits {
+                max_mints_per_player_per_minute,
+                max_tokens_per_player_per_hour,
+                max_tokens_per_player_per_day,
+            } => {
+                self.max_mints_per_player_per_minute = max_mints_per_player_per_minute;
+                self.max_tokens_per_player_per_hour = max_tokens_per_player_per_hour;
+                self.max_tokens_per_player_per_day = max_tokens_per_player_per_day;
+            }
+            ParameterChange::TimelockDelay { delay } => {
+                self.timelock_delay = delay;
+            }
+            ParameterChange::EmissionSchedule {
+                epoch_length,
+                initial_per_epoch,
+                decay_bps,
+                decay_interval,
+            } => {
+                // A newly enabled schedule starts now; an active one applies the new curve
+                // from the last crank onwards. A new epoch length re-anchors emission_start
+                // so the epoch at the last crank keeps its number (and decay step)
+                if !self.emission_enabled() {
+                    self.emission_start = current_time;
+                    self.last_mint_timestamp = current_time;
+                } else if epoch_length != self.emission_epoch_length {
+                    let epoch = self.emission_epoch_at(self.last_mint_timestamp) as i64;
+                    self.emission_start = epoch
+                        .checked_mul(epoch_length)
+                        .and_then(|elapsed| self.last_mint_timestamp.checked_sub(elapsed))
+                        .ok_or(GameTokenError::MathOverflow)?;
+                }
+                self.emission_epoch_length = epoch_length;
+                self.emission_initial_per_epoch = initial_per_epoch;
+                self.emission_decay_bps = decay_bps;
+                self.emission_decay_interval = decay_interval;
+            }
+            // Lives in SplitConfig, applied by execute_parameter_change
+            ParameterChange::MintSplit { .. } => {
+                return err!(GameTokenError::InvalidParameterChange);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn initialize(
+        &mut self,
+        owner: Pubkey,
+        max_mints_per_player_per_minute: u8,
+        is_infinite: bool,
+        max_supply: u64,
+        bump: u8,
+    ) {
+        self.owner = owner;
+        self.total_minted = 0;
+        self.is_infinite = is_infinite;
+        self.max_supply = max_supply;
+        self.max_mints_per_player_per_minute = max_mints_per_player_per_minute;
+        self.bump = bump;
+        self.minters = Vec::new();
+        self.max_tokens_per_player_per_hour = 0;
+        self.max_tokens_per_player_per_day = 0;
+        self.pause_flags = 0;
+        self.pause_reason = 0;
+        self.paused_at = 0;
+        self.pending_owner = Pubkey::default();
+        self.timelock_delay = 0;
+        self.timelock_count = 0;
+        self.emission_epoch_length = 0;
+        self.emission_initial_per_epoch = 0;
+        self.emission_decay_bps = BPS_DENOMINATOR as u16;
+        self.emission_decay_interval = 1;
+        self.emission_start = 0;
+        self.last_mint_timestamp = 0;
+        self.owner_vesting_token_account = Pubkey::default();
+        self.supply_cap_mode = SupplyCapMode::TotalMinted;
+        self.max_supply_locked = false;
+        self.total_burned = 0;
+        self.last_reconciled_at = 0;
+    }
+
+    pub fn minter_index(&self, minter: &Pubkey) -> Option<usize> {
+        self.minters.iter().position(|entry| entry.minter == *minter)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct MinterQuota {
+    pub minter: Pubkey,
+    pub quota: u64,
+    pub minted: u64,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PlayerMintStats {
+    pub player: Pubkey,
+    // Tokens earned across all completed sessions
+    pub session_tokens: u64,
+    pub last_mint_minute: i64,
+    pub mints_this_minute: u8,
+    pub total_earned: u64,
+    pub total_claimed: u64,
+    pub bump: u8,
+    pub last_voucher_nonce: u64,
+    pub last_earn_hour: i64,
+    pub tokens_this_hour: u64,
+    pub last_earn_day: i64,
+    pub tokens_this_day: u64,
+    pub total_transfer_fees: u64,
+    // Lifetime session totals rolled up by end_session
+    pub sessions_completed: u64,
+    pub total_session_seconds: u64,
+    pub best_session_tokens: u64,
+    // Set once by register_referrer
+    pub referrer: Pubkey,
+    // Daily check-in streak, see check_in
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub last_check_in_day: i64,
+    pub total_check_ins: u64,
+    pub streak_freezes: u8,
+}
+
+impl PlayerMintStats {
+    // Earned tokens not yet withdrawn through player_claim_tokens
+    pub fn claimable(&self) -> u64 {
+        self.total_earned.saturating_sub(self.total_claimed)
+    }
+
+    // Records a check-in on UTC day `today` and returns the streak freezes spent.
+    // The next day extends the streak; a gap is bridged only if the held freezes
+    // cover every missed day, otherwise the streak restarts at 1
+    pub fn record_check_in(&mut self, today: i64) -> Result<u8> {
+        require!(self.last_check_in_day < today, GameTokenError::AlreadyCheckedIn);
+
+        let missed_days = today - self.last_check_in_day - 1;
+        let mut freezes_used = 0u8;
+        if self.current_streak > 0 && missed_days <= self.streak_freezes as i64 {
+            freezes_used = missed_days as u8;
+            self.streak_freezes -= freezes_used;
+            self.current_streak = self.current_streak.saturating_add(1);
+        } else {
+            self.current_streak = 1;
+        }
+        self.longest_streak = self.longest_streak.max(self.current_streak);
+        self.last_check_in_day = today;
+        self.total_check_ins = self
+            .total_check_ins
+            .checked_add(1)
+            .ok_or(GameTokenError::MathOverflow)?;
+        Ok(freezes_used)
+    }
+
+    // Rolls the minute/hour/day windows forward, then rejects the earn if any
+    // configured limit would be exceeded. Logs the cooldown for the client.
+    pub fn apply_rate_limits(
+        &mut self,
+        limits: &MintingAuthority,
+        amount: u64,
+        current_time: i64,
+    ) -> Result<()> {
+        let current_minute = current_time / 60;
+        let current_hour = current_time / 3_600;
+        let current_day = current_time / 86_400;
+
+        if current_minute > self.last_mint_minute {
+            self.last_mint_minute = current_minute;
+            self.mints_this_minute = 0;
+        }
+        if current_hour > self.last_earn_hour {
+            self.last_earn_hour = current_hour;
+            self.tokens_this_hour = 0;
+        }
+        if current_day > self.last_earn_day {
+            self.last_earn_day = current_day;
+            self.tokens_this_day = 0;
+        }
+
+        let max_per_minute = limits.max_mints_per_player_per_minute;
+        if max_per_minute > 0 && self.mints_this_minute >= max_per_minute {
+            msg!(
+                "Limit {} earns/minute reached, retry after {}s",
+                max_per_minute,
+                (current_minute + 1) * 60 - current_time
+            );
+            return err!(GameTokenError::PlayerRateLimitExceeded);
+        }
+
+        let tokens_this_hour = self
+            .tokens_this_hour
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        let max_per_hour = limits.max_tokens_per_player_per_hour;
+        if max_per_hour > 0 && tokens_this_hour > max_per_hour {
+            msg!(
+                "Limit {} tokens/hour reached ({} earned), retry after {}s",
+                max_per_hour,
+                self.tokens_this_hour,
+                (current_hour + 1) * 3_600 - current_time
+            );
+            return err!(GameTokenError::PlayerHourlyCapExceeded);
+        }
+
+        let tokens_this_day = self
+            .tokens_this_day
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        let max_per_day = limits.max_tokens_per_player_per_day;
+        if max_per_day > 0 && tokens_this_day > max_per_day {
+            msg!(
+                "Limit {} tokens/day reached ({} earned), retry after {}s",
+                max_per_day,
+                self.tokens_this_day,
+                (current_day + 1) * 86_400 - current_time
+            );
+            return err!(GameTokenError::PlayerDailyCapExceeded);
+        }
+
+        self.mints_this_minute = self.mints_this_minute.saturating_add(1);
+        self.tokens_this_hour = tokens_this_hour;
+        self.tokens_this_day = tokens_this_day;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct GameTokenPools {
+    pub authority: Pubkey,
+    pub active_pool: u64,
+    pub reward_pool: u64,
+    pub reserve_pool: u64,
+    pub burn_pool: u64,
+    pub game_token_mint: Pubkey,
+    pub bump: u8,
+    pub game_server: Pubkey,
+    pub pending_authority: Pubkey,
+    pub reward_pool_token_account: Pubkey,
+    pub reserve_pool_token_account: Pubkey,
+    pub burn_pool_token_account: Pubkey,
+    pub max_pool_transfer_amount: u64,
+    pub max_pool_transfer_per_day: u64,
+    pub pool_transfer_day: i64,
+    pub pool_transferred_today: u64,
+    // Global earn budget across all players, reset every earn_epoch_length seconds
+    pub earn_budget_per_epoch: u64,
+    pub earn_epoch_length: i64,
+    pub earn_epoch: i64,
+    pub earned_this_epoch: u64,
+    // Reserve -> active top-up thresholds used by rebalance_pools
+    pub active_low_watermark: u64,
+    pub active_high_watermark: u64,
+    pub max_rebalance_per_call: u64,
+    // Per-session limits applied by start_session, 0 disables a limit
+    pub session_earn_cap: u64,
+    pub max_session_duration: i64,
+    // Cap on tokens earned per day across all of a player's sessions
+    pub session_daily_cap: u64,
+    // Earned but unclaimed player balances; reserved out of active_pool, still held
+    // in the game pools token account
+    pub outstanding_claims: u64,
+    // Referral bonus paid to referrers from reward_pool, on earns or on claims;
+    // caps of 0 are unlimited
+    pub referral_bonus_bps: u16,
+    pub referral_pay_on_claim: bool,
+    pub max_referral_bonus_per_referee: u64,
+    pub max_referral_bonus_per_referrer: u64,
+    // Latest season opened by open_season and whether it is still open
+    pub current_season_id: u32,
+    pub season_active: bool,
+}
+
+impl GameTokenPools {
+    pub fn initialize(&mut self, authority: Pubkey, game_token_mint: Pubkey, bump: u8) {
+        self.authority = authority;
+        self.active_pool = 0;
+        self.reward_pool = 0;
+        self.reserve_pool = 0;
+        self.burn_pool = 0;
+        self.game_token_mint = game_token_mint;
+        self.bump = bump;
+        self.game_server = Pubkey::default();
+        self.pending_authority = Pubkey::default();
+        self.reward_pool_token_account = Pubkey::default();
+        self.reserve_pool_token_account = Pubkey::default();
+        self.burn_pool_token_account = Pubkey::default();
+        self.max_pool_transfer_amount = 0;
+        self.max_pool_transfer_per_day = 0;
+        self.pool_transfer_day = 0;
+        self.pool_transferred_today = 0;
+        self.earn_budget_per_epoch = 0;
+        self.earn_epoch_length = 86_400;
+        self.earn_epoch = 0;
+        self.earned_this_epoch = 0;
+        self.active_low_watermark = 0;
+        self.active_high_watermark = 0;
+        self.max_rebalance_per_call = 0;
+        self.session_earn_cap = 0;
+        self.max_session_duration = 0;
+        self.session_daily_cap = 0;
+        self.outstanding_claims = 0;
+        self.referral_bonus_bps = 0;
+        self.referral_pay_on_claim = false;
+        self.max_referral_bonus_per_referee = 0;
+        self.max_referral_bonus_per_referrer = 0;
+        self.current_season_id = 0;
+        self.season_active = false;
+    }
+
+    pub fn pool_balance(&self, kind: PoolKind) -> u64 {
+        match kind {
+            PoolKind::Active => self.active_pool,
+            PoolKind::Reward => self.reward_pool,
+            PoolKind::Reserve => self.reserve_pool,
+            PoolKind::Burn => self.burn_pool,
+        }
+    }
+
+    pub fn pool_balance_mut(&mut self, kind: PoolKind) -> &mut u64 {
+        match kind {
+            PoolKind::Active => &mut self.active_pool,
+            PoolKind::Reward => &mut self.reward_pool,
+            PoolKind::Reserve => &mut self.reserve_pool,
+            PoolKind::Burn => &mut self.burn_pool,
+        }
+    }
+
+    // Credits every pool that received a mint split share; returns the active pool share
+    pub fn credit_minted_shares(
+        &mut self,
+        split_config: &SplitConfig,
+        shares: &[u64],
+        active_pool_token_account: &Pubkey,
+    ) -> Result<u64> {
+        let credits = [
+            (PoolKind::Active, *active_pool_token_account),
+            (PoolKind::Reward, self.reward_pool_token_account),
+            (PoolKind::Reserve, self.reserve_pool_token_account),
+            (PoolKind::Burn, self.burn_pool_token_account),
+        ];
+        for (kind, token_account) in credits {
+            let share = split_config.share_of(shares, &token_account);
+            let balance = self.pool_balance_mut(kind);
+            *balance = balance
+                .checked_add(share)
+                .ok_or(GameTokenError::MathOverflow)?;
+        }
+        Ok(split_config.share_of(shares, active_pool_token_account))
+    }
+
+    // What players can still earn this epoch, u64::MAX when unlimited
+    pub fn remaining_earn_budget(&self, current_time: i64) -> u64 {
+        if self.earn_budget_per_epoch == 0 {
+            return u64::MAX;
+        }
+        let earned = if current_time / self.earn_epoch_length.max(1) > self.earn_epoch {
+            0
+        } else {
+            self.earned_this_epoch
+        };
+        self.earn_budget_per_epoch.saturating_sub(earned)
+    }
+
+    // Rolls the earn epoch forward if needed and takes `amount` from its budget
+    pub fn consume_earn_budget(&mut self, amount: u64, current_time: i64) -> Result<()> {
+        let current_epoch = current_time / self.earn_epoch_length.max(1);
+        if current_epoch > self.earn_epoch {
+            self.earn_epoch = current_epoch;
+            self.earned_this_epoch = 0;
+        }
+
+        let earned = self
+            .earned_this_epoch
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        if self.earn_budget_per_epoch > 0 && earned > self.earn_budget_per_epoch {
+            msg!(
+                "Earn budget exhausted: {} of {} left, resets in {}s",
+                self.earn_budget_per_epoch - self.earned_this_epoch,
+                self.earn_budget_per_epoch,
+                (current_epoch + 1) * self.earn_epoch_length - current_time
+            );
+            return err!(GameTokenError::EarnBudgetExhausted);
+        }
+        self.earned_this_epoch = earned;
+        Ok(())
+    }
+
+    // Checks the per-transfer and per-day limits and records the transfer
+    pub fn record_pool_transfer(&mut self, amount: u64, current_time: i64) -> Result<()> {
+        let current_day = current_time / 86_400;
+        if current_day > self.pool_transfer_day {
+            self.pool_transfer_day = current_day;
+            self.pool_transferred_today = 0;
+        }
+
+        require!(
+            self.max_pool_transfer_amount == 0 || amount <= self.max_pool_transfer_amount,
+            GameTokenError::PoolTransferLimitExceeded
+        );
+        let transferred_today = self
+            .pool_transferred_today
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        require!(
+            self.max_pool_transfer_per_day == 0
+                || transferred_today <= self.max_pool_transfer_per_day,
+            GameTokenError::PoolTransferLimitExceeded
+        );
+        self.pool_transferred_today = transferred_today;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolKind {
+    Active,
+    Reward,
+    Reserve,
+    Burn,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Multisig {
+    pub create_key: Pubkey,
+    #[max_len(MAX_MULTISIG_OWNERS)]
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub owner_set_seqno: u32,
+    pub transaction_count: u64,
+    pub bump: u8,
+    pub signer_bump: u8,
+}
+
+impl Multisig {
+    pub fn owner_index(&self, owner: &Pubkey) -> Option<usize> {
+        self.owners.iter().position(|key| key == owner)
+    }
+
+    // Approval flags for a new proposal, with the proposer's approval already set
+    pub fn proposal_signers(&self, proposer: &Pubkey) -> Result<Vec<bool>> {
+        let owner_index = self
+            .owner_index(proposer)
+            .ok_or(GameTokenError::NotMultisigOwner)?;
+        let mut signers = vec![false; self.owners.len()];
+        signers[owner_index] = true;
+        Ok(signers)
+    }
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct MultisigTransaction {
+    pub multisig: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub program_id: Pubkey,
+    #[max_len(MAX_MULTISIG_TX_ACCOUNTS)]
+    pub accounts: Vec<TransactionAccount>,
+    #[max_len(MAX_MULTISIG_TX_DATA)]
+    pub data: Vec<u8>,
+    #[max_len(MAX_MULTISIG_OWNERS)]
+    pub signers: Vec<bool>,
+    pub owner_set_seqno: u32,
+    pub did_execute: bool,
+    pub bump: u8,
+}
+
+impl MultisigTransaction {
+    pub fn approvals(&self) -> u8 {
+        self.signers.iter().filter(|approved| **approved).count() as u8
+    }
+
+    // Pending and proposed under the multisig's current owner set
+    pub fn check_pending(&self, multisig: &Multisig) -> Result<()> {
+        require!(!self.did_execute, GameTokenError::TransactionAlreadyExecuted);
+        require!(
+            self.owner_set_seqno == multisig.owner_set_seqno,
+            GameTokenError::StaleTransaction
+        );
+        Ok(())
+    }
+
+    // Records `owner`'s approval and returns the approval count
+    pub fn approve(&mut self, multisig: &Multisig, owner: &Pubkey) -> Result<u8> {
+        self.check_pending(multisig)?;
+        let owner_index = multisig
+            .owner_index(owner)
+            .ok_or(GameTokenError::NotMultisigOwner)?;
+        self.signers[owner_index] = true;
+        Ok(self.approvals())
+    }
+
+    pub fn check_executable(&self, multisig: &Multisig) -> Result<()> {
+        self.check_pending(multisig)?;
+        require!(
+            self.approvals() >= multisig.threshold,
+            GameTokenError::NotEnoughApprovals
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct TransactionAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+// Economic parameters that can only change through the timelock
+// Rate limits of 0 disable the corresponding limit
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub enum ParameterChange {
+    SupplyCap {
+        is_infinite: bool,
+        max_supply: u64,
+        cap_mode: SupplyCapMode,
+    },
+    RateLimits {
+        max_mints_per_player_per_minute: u8,
+        max_tokens_per_player_per_hour: u64,
+        max_tokens_per_player_per_day: u64,
+    },
+    TimelockDelay {
+        delay: i64,
+    },
+    MintSplit {
+        #[max_len(MAX_SPLIT_RECIPIENTS)]
+        recipients: Vec<SplitRecipient>,
+        dust_recipient: u8,
+    },
+    // initial_per_epoch of 0 disables emission
+    EmissionSchedule {
+        epoch_length: i64,
+        initial_per_epoch: u64,
+        decay_bps: u16,
+        decay_interval: u32,
+    },
+}
+
+impl Default for ParameterChange {
+    fn default() -> Self {
+        ParameterChange::TimelockDelay { delay: 0 }
+    }
+}
+
+impl ParameterChange {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            ParameterChange::TimelockDelay { delay } => {
+                require!(*delay >= 0, GameTokenError::InvalidParameterChange);
+            }
+            ParameterChange::MintSplit { recipients, dust_recipient } => {
+                validate_split(recipients, *dust_recipient)?;
+            }
+            ParameterChange::EmissionSchedule {
+                epoch_length,
+                decay_bps,
+                decay_interval,
+                ..
+            } => {
+                require!(
+                    *epoch_length > 0
+                        && *decay_bps as u64 <= BPS_DENOMINATOR
+                        && *decay_interval > 0,
+                    GameTokenError::InvalidParameterChange
+                );
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+// TotalMinted caps everything ever minted (burns do not free room),
+// Circulating caps the mint's current supply
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq, InitSpace)]
+pub enum SupplyCapMode {
+    #[default]
+    TotalMinted,
+    Circulating,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum TimelockStatus {
+    #[default]
+    Queued,
+    Executed,
+    Cancelled,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct QueuedParameterChange {
+    pub index: u64,
+    pub change: ParameterChange,
+    pub proposer: Pubkey,
+    pub queued_at: i64,
+    pub eta: i64,
+    pub status: TimelockStatus,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq, Eq, InitSpace)]
+pub struct SplitRecipient {
+    pub token_account: Pubkey,
+    pub weight_bps: u16,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct SplitConfig {
+    #[max_len(MAX_SPLIT_RECIPIENTS)]
+    pub recipients: Vec<SplitRecipient>,
+    pub dust_recipient: u8,
+    pub bump: u8,
+}
+
+impl SplitConfig {
+    // Share per recipient, in config order. Rounding remainder goes to the dust recipient
+    pub fn compute_shares(&self, amount: u64) -> Result<Vec<u64>> {
+        let mut shares = Vec::with_capacity(self.recipients.len());
+        let mut distributed: u64 = 0;
+        for recipient in self.recipients.iter() {
+            let share = (amount as u128 * recipient.weight_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+            distributed += share;
+            shares.push(share);
+        }
+
+        let dust = amount
+            .checked_sub(distributed)
+            .ok_or(GameTokenError::MathOverflow)?;
+        let dust_share = shares
+            .get_mut(self.dust_recipient as usize)
+            .ok_or(GameTokenError::InvalidSplitConfig)?;
+        *dust_share += dust;
+        Ok(shares)
+    }
+
+    // Total share going to `token_account`
+    pub fn share_of(&self, shares: &[u64], token_account: &Pubkey) -> u64 {
+        self.recipients
+            .iter()
+            .zip(shares.iter())
+            .filter(|(recipient, _)| recipient.token_account == *token_account)
+            .map(|(_, share)| *share)
+            .sum()
+    }
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct StakePool {
+    pub vault: Pubkey,
+    pub total_staked: u64,
+    // Rewards per staked token since launch, scaled by REWARD_PRECISION
+    pub reward_per_token_stored: u128,
+    pub last_update_time: i64,
+    pub apr_bps: u16,
+    pub unstake_cooldown: i64,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+impl StakePool {
+    pub fn update_rewards(&mut self, current_time: i64) {
+        if current_time <= self.last_update_time {
+            return;
+        }
+        let elapsed = (current_time - self.last_update_time) as u128;
+        self.reward_per_token_stored += self.apr_bps as u128 * elapsed * REWARD_PRECISION
+            / (BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR);
+        self.last_update_time = current_time;
+    }
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PlayerStake {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub reward_per_token_paid: u128,
+    pub pending_rewards: u64,
+    pub total_rewards_claimed: u64,
+    // Sum of unstake_requests
+    pub unstaking_amount: u64,
+    pub bump: u8,
+    #[max_len(MAX_UNSTAKE_REQUESTS)]
+    pub unstake_requests: Vec<UnstakeRequest>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct UnstakeRequest {
+    pub amount: u64,
+    pub available_at: i64,
+}
+
+impl PlayerStake {
+    // Moves rewards accrued since the last settlement into pending_rewards
+    pub fn settle_rewards(&mut self, stake_pool: &StakePool) -> Result<()> {
+        let delta = stake_pool.reward_per_token_stored - self.reward_per_token_paid;
+        let earned = self.amount as u128 * delta / REWARD_PRECISION;
+        self.pending_rewards = u64::try_from(earned)
+            .ok()
+            .and_then(|earned| self.pending_rewards.checked_add(earned))
+            .ok_or(GameTokenError::MathOverflow)?;
+        self.reward_per_token_paid = stake_pool.reward_per_token_stored;
+        Ok(())
+    }
+
+    // Queues `amount` until `available_at`; earlier requests keep their own cooldown
+    pub fn request_unstake(&mut self, amount: u64, available_at: i64) -> Result<()> {
+        self.unstaking_amount = self
+            .unstaking_amount
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        match self.unstake_requests.last_mut() {
+            Some(last) if last.available_at == available_at => last.amount += amount,
+            _ => {
+                require!(
+                    self.unstake_requests.len() < MAX_UNSTAKE_REQUESTS,
+                    GameTokenError::TooManyUnstakeRequests
+                );
+                self.unstake_requests.push(UnstakeRequest { amount, available_at });
+            }
+        }
+        Ok(())
+    }
+
+    // Total of the requests whose cooldown has passed
+    pub fn withdrawable(&self, current_time: i64) -> u64 {
+        self.unstake_requests
+            .iter()
+            .filter(|request| current_time >= request.available_at)
+            .map(|request| request.amount)
+            .sum()
+    }
+
+    pub fn release_withdrawable(&mut self, current_time: i64) {
+        self.unstaking_amount -= self.withdrawable(current_time);
+        self.unstake_requests
+            .retain(|request| current_time < request.available_at);
+    }
+}
+
+// Part of the escrow vesting linearly from `start` over the escrow's duration
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq, InitSpace)]
+pub struct VestingTranche {
+    pub start: i64,
+    pub amount: u64,
+    pub vested: u64,
+}
+
+// Escrow for the owner's mint share. Each deposit vests linearly over
+// `duration_seconds` after a `cliff_seconds` delay. Deposits made within the same
+// `tranche_interval` share a tranche whose cliff counts from the end of that
+// interval, so no deposit vests early and tranches already vesting are never
+// touched. The interval is sized so a tranche is fully vested before
+// MAX_VESTING_TRANCHES newer ones exist, which keeps the account a fixed size.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct VestingEscrow {
+    pub token_account: Pubkey,
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+    pub tranche_interval: i64,
+    #[max_len(MAX_VESTING_TRANCHES)]
+    pub tranches: Vec<VestingTranche>,
+    pub unvested: u64,
+    pub vested_unreleased: u64,
+    pub total_deposited: u64,
+    pub total_released: u64,
+    pub bump: u8,
+}
+
+impl VestingEscrow {
+    // Tranche interval such that a tranche is fully vested before MAX_VESTING_TRANCHES
+    // newer ones can exist
+    pub fn tranche_interval_for(cliff_seconds: i64, duration_seconds: i64) -> Result<i64> {
+        let schedule_length = cliff_seconds
+            .checked_add(duration_seconds)
+            .ok_or(GameTokenError::MathOverflow)?;
+        let slots = MAX_VESTING_TRANCHES as i64 - 1;
+        Ok(((schedule_length + slots - 1) / slots).max(1))
+    }
+
+    // Moves whatever has vested by `current_time` to vested_unreleased and drops
+    // fully vested tranches
+    pub fn accrue(&mut self, current_time: i64) {
+        let duration = self.duration_seconds;
+        for tranche in self.tranches.iter_mut() {
+            if current_time <= tranche.start {
+                continue;
+            }
+            let elapsed = current_time - tranche.start;
+            let vested_now = if elapsed >= duration {
+                tranche.amount
+            } else {
+                (tranche.amount as u128 * elapsed as u128 / duration as u128) as u64
+            };
+            let vested = vested_now - tranche.vested;
+            tranche.vested = vested_now;
+            self.unvested -= vested;
+            self.vested_unreleased += vested;
+        }
+        self.tranches.retain(|tranche| tranche.vested < tranche.amount);
+    }
+
+    pub fn deposit(&mut self, amount: u64, current_time: i64) -> Result<()> {
+        self.accrue(current_time);
+
+        let interval = self.tranche_interval;
+        let start = (current_time.div_euclid(interval) + 1)
+            .checked_mul(interval)
+            .and_then(|interval_end| interval_end.checked_add(self.cliff_seconds))
+            .ok_or(GameTokenError::MathOverflow)?;
+        match self.tranches.last_mut() {
+            Some(tranche) if tranche.start == start => {
+                tranche.amount = tranche
+                    .amount
+                    .checked_add(amount)
+                    .ok_or(GameTokenError::MathOverflow)?;
+            }
+            _ => {
+                require!(
+                    self.tranches.len() < MAX_VESTING_TRANCHES,
+                    GameTokenError::TooManyVestingTranches
+                );
+                self.tranches.push(VestingTranche { start, amount, vested: 0 });
+            }
+        }
+
+        self.unvested = self
+            .unvested
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        self.total_deposited = self
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        Ok(())
+    }
+}
+
+// Token-2022 metadata written onto the game mint by initialize_game_mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GameMintMetadata {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PlayerSession {
+    pub player: Pubkey,
+    pub is_open: bool,
+    pub started_at: i64,
+    // 0 means no expiry / no cap, copied from GameTokenPools at start_session
+    pub expires_at: i64,
+    pub earn_cap: u64,
+    pub tokens_earned: u64,
+    pub session_count: u64,
+    pub bump: u8,
+    // Earnings of the current day across sessions, checked against daily_cap
+    // (copied from GameTokenPools at start_session, 0 disables)
+    pub daily_cap: u64,
+    pub earn_day: i64,
+    pub tokens_today: u64,
+}
+
+impl PlayerSession {
+    pub fn record_earn(&mut self, amount: u64, current_time: i64) -> Result<()> {
+        require!(self.is_open, GameTokenError::SessionNotOpen);
+        require!(
+            self.expires_at == 0 || current_time <= self.expires_at,
+            GameTokenError::SessionExpired
+        );
+        let tokens_earned = self
+            .tokens_earned
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        require!(
+            self.earn_cap == 0 || tokens_earned <= self.earn_cap,
+            GameTokenError::SessionCapExceeded
+        );
+
+        // Restarting sessions does not reset the daily total
+        let today = current_time / SECONDS_PER_DAY;
+        let tokens_today = if self.earn_day == today { self.tokens_today } else { 0 };
+        let tokens_today = tokens_today
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        require!(
+            self.daily_cap == 0 || tokens_today <= self.daily_cap,
+            GameTokenError::SessionDailyCapExceeded
+        );
+
+        self.tokens_earned = tokens_earned;
+        self.earn_day = today;
+        self.tokens_today = tokens_today;
+        Ok(())
+    }
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Achievement {
+    pub id: u16,
+    pub reward_amount: u64,
+    // 0 means no expiry
+    pub expires_at: i64,
+    pub is_active: bool,
+    pub unlock_count: u64,
+    pub bump: u8,
+}
+
+// Per-player bitmap of unlocked achievements, bit `id` set once unlocked
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PlayerAchievements {
+    pub player: Pubkey,
+    pub unlocked: [u8; MAX_ACHIEVEMENTS / 8],
+    pub unlocked_count: u16,
+    pub bump: u8,
+}
+
+impl PlayerAchievements {
+    pub fn is_unlocked(&self, achievement_id: u16) -> bool {
+        let id = achievement_id as usize;
+        self.unlocked[id / 8] & (1 << (id % 8)) != 0
+    }
+
+    pub fn unlock(&mut self, achievement_id: u16) {
+        let id = achievement_id as usize;
+        self.unlocked[id / 8] |= 1 << (id % 8);
+        self.unlocked_count += 1;
+    }
+}
+
+// Referral relation, one per referred player
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Referral {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+    pub registered_at: i64,
+    // Bonus paid to the referrer on account of this player
+    pub bonus_paid: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct ReferrerStats {
+    pub referrer: Pubkey,
+    pub referee_count: u64,
+    pub total_bonus_paid: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Season {
+    pub id: u32,
+    pub start_time: i64,
+    pub end_time: i64,
+    // 0 means no budget
+    pub reward_budget: u64,
+    pub total_earned: u64,
+    pub participant_count: u64,
+    pub is_closed: bool,
+    pub closed_at: i64,
+    pub bump: u8,
+    pub leaderboard_enabled: bool,
+}
+
+impl Season {
+    pub fn is_running(&self, current_time: i64) -> bool {
+        !self.is_closed && current_time >= self.start_time && current_time < self.end_time
+    }
+
+    // Adds an earning to the season and the player's season stats; earnings outside
+    // the season window are not attributed. Returns whether it was attributed
+    pub fn record_earn(
+        &mut self,
+        stats: &mut PlayerSeasonStats,
+        amount: u64,
+        current_time: i64,
+    ) -> Result<bool> {
+        if !self.is_running(current_time) {
+            return Ok(false);
+        }
+        let total_earned = self
+            .total_earned
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        require!(
+            self.reward_budget == 0 || total_earned <= self.reward_budget,
+            GameTokenError::SeasonBudgetExhausted
+        );
+        self.total_earned = total_earned;
+
+        if stats.earn_count == 0 {
+            self.participant_count += 1;
+        }
+        stats.tokens_earned = stats
+            .tokens_earned
+            .checked_add(amount)
+            .ok_or(GameTokenError::MathOverflow)?;
+        stats.earn_count += 1;
+        stats.last_earned_at = current_time;
+        Ok(true)
+    }
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct PlayerSeasonStats {
+    pub player: Pubkey,
+    pub season_id: u32,
+    pub tokens_earned: u64,
+    pub earn_count: u64,
+    pub last_earned_at: i64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, PartialEq, Eq, InitSpace)]
+pub struct LeaderboardEntry {
+    pub player: Pubkey,
+    pub tokens_earned: u64,
+}
+
+// Top LEADERBOARD_SIZE players of a season by season earnings, highest first
+#[account]
+#[derive(Default, InitSpace)]
+pub struct Leaderboard {
+    pub season_id: u32,
+    #[max_len(LEADERBOARD_SIZE)]
+    pub entries: Vec<LeaderboardEntry>,
+    #[max_len(LEADERBOARD_SIZE)]
+    pub prize_table: Vec<u64>,
+    pub prizes_distributed: bool,
+    pub bump: u8,
+}
+
+impl Leaderboard {
+    // Updates the player's season total, inserting them if they beat the last entry
+    pub fn record(&mut self, player: Pubkey, tokens_earned: u64) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.player == player) {
+            entry.tokens_earned = tokens_earned;
+        } else if self.entries.len() < LEADERBOARD_SIZE {
+            self.entries.push(LeaderboardEntry { player, tokens_earned });
+        } else if let Some(last) = self.entries.last_mut() {
+            if tokens_earned <= last.tokens_earned {
+                return;
+            }
+            *last = LeaderboardEntry { player, tokens_earned };
+        }
+        // Stable sort keeps earlier arrivals ahead on ties
+        self.entries.sort_by_key(|entry| std::cmp::Reverse(entry.tokens_earned));
+    }
+}
+
+#[account]
+#[derive(Default, InitSpace)]
+pub struct StreakConfig {
+    #[max_len(MAX_STREAK_TABLE)]
+    pub reward_table: Vec<u64>,
+    pub freeze_price: u64,
+    pub max_freezes: u8,
+    pub bump: u8,
+}
+
+impl StreakConfig {
+    // Reward for day `streak` of a streak; the last table entry applies beyond its end
+    pub fn reward_for(&self, streak: u32) -> u64 {
+        let index = (streak.max(1) as usize - 1).min(self.reward_table.len().saturating_sub(1));
+        self.reward_table.get(index).copied().unwrap_or(0)
+    }
+}
+
+// Events
+#[event]
+pub struct AutoMintEvent {
+    pub minter: Pubkey,
+    pub amount: u64,
+    pub game_amount: u64,
+    // Owner share: deposited into the vesting escrow vs minted straight to the owner
+    pub vested_amount: u64,
+    pub released_amount: u64,
+    pub shares: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlayerEarnedEvent {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub nonce: u64,
+    pub claimable: u64,
+    pub remaining_pool: u64,
+    pub remaining_earn_budget: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PlayerClaimedEvent {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub net_amount: u64,
+    pub remaining_claimable: u64,
+    pub outstanding_claims: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultisigCreatedEvent {
+    pub multisig: Pubkey,
+    pub multisig_signer: Pubkey,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MultisigOwnersChangedEvent {
+    pub multisig: Pubkey,
+    pub owners: Vec<Pubkey>,
+    pub threshold: u8,
+    pub owner_set_seqno: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransactionProposedEvent {
+    pub multisig: Pubkey,
+    pub transaction: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub program_id: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransactionApprovedEvent {
+    pub multisig: Pubkey,
+    pub transaction: Pubkey,
+    pub owner: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransactionExecutedEvent {
+    pub multisig: Pubkey,
+    pub transaction: Pubkey,
+    pub index: u64,
+    pub executor: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SplitConfigUpdatedEvent {
+    pub recipients: Vec<SplitRecipient>,
+    pub dust_recipient: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmissionCrankedEvent {
+    pub cranker: Pubkey,
+    pub amount: u64,
+    pub forfeited: u64,
+    pub game_amount: u64,
+    pub vested_amount: u64,
+    pub released_amount: u64,
+    pub shares: Vec<u64>,
+    pub from: i64,
+    pub to: i64,
+    pub epoch: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolTransferLimitsUpdatedEvent {
+    pub max_pool_transfer_amount: u64,
+    pub max_pool_transfer_per_day: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolTransferEvent {
+    pub from: PoolKind,
+    pub to: PoolKind,
+    pub amount: u64,
+    pub fee: u64,
+    pub from_balance: u64,
+    pub to_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolBurnEvent {
+    pub amount: u64,
+    pub remaining_burn_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakingParamsUpdatedEvent {
+    pub apr_bps: u16,
+    pub unstake_cooldown: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakedEvent {
+    pub player: Pubkey,
+    // Amount sent; the stake is credited amount - fee
+    pub amount: u64,
+    pub fee: u64,
+    pub staked: u64,
+    pub total_staked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnstakeRequestedEvent {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub staked: u64,
+    pub available_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnstakeWithdrawnEvent {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StakeRewardsClaimedEvent {
+    pub player: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub pending_rewards: u64,
+    pub remaining_reward_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerVestingInitializedEvent {
+    pub token_account: Pubkey,
+    pub cliff_seconds: i64,
+    pub duration_seconds: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestedReleasedEvent {
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub total_released: u64,
+    pub unvested: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EarnBudgetUpdatedEvent {
+    pub earn_budget_per_epoch: u64,
+    pub earn_epoch_length: i64,
+    pub remaining_earn_budget: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RebalanceConfigUpdatedEvent {
+    pub active_low_watermark: u64,
+    pub active_high_watermark: u64,
+    pub max_rebalance_per_call: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PoolsRebalancedEvent {
+    pub caller: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub active_pool: u64,
+    pub reserve_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TransferFeesHarvestedEvent {
+    pub destination: PoolKind,
+    pub amount: u64,
+    pub pool_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GameMintInitializedEvent {
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub decimals: u8,
+    pub freeze_authority: Option<Pubkey>,
+    pub has_metadata: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MaxSupplyLockedEvent {
+    pub max_supply: u64,
+    pub cap_mode: SupplyCapMode,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SupplyReconciledEvent {
+    pub mint_supply: u64,
+    pub total_minted: u64,
+    pub total_burned: u64,
+    pub newly_burned: u64,
+    pub unaccounted_supply: u64,
+    // mint supply minus tracked circulating supply, before recording burns
+    pub drift: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SessionConfigUpdatedEvent {
+    pub session_earn_cap: u64,
+    pub max_session_duration: i64,
+    pub session_daily_cap: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SessionStartedEvent {
+    pub player: Pubkey,
+    pub session_id: u64,
+    pub earn_cap: u64,
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SessionEndedEvent {
+    pub player: Pubkey,
+    pub session_id: u64,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub tokens_earned: u64,
+    pub earn_cap: u64,
+    pub sessions_completed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AchievementUpdatedEvent {
+    pub achievement_id: u16,
+    pub reward_amount: u64,
+    pub expires_at: i64,
+    pub is_active: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AchievementUnlockedEvent {
+    pub player: Pubkey,
+    pub achievement_id: u16,
+    pub reward_amount: u64,
+    pub fee: u64,
+    pub unlocked_count: u16,
+    pub remaining_reward_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralConfigUpdatedEvent {
+    pub referral_bonus_bps: u16,
+    pub referral_pay_on_claim: bool,
+    pub max_referral_bonus_per_referee: u64,
+    pub max_referral_bonus_per_referrer: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferrerRegisteredEvent {
+    pub player: Pubkey,
+    pub referrer: Pubkey,
+    pub referee_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReferralBonusPaidEvent {
+    pub referrer: Pubkey,
+    pub referee: Pubkey,
+    pub source_amount: u64,
+    pub bonus: u64,
+    pub fee: u64,
+    pub referee_bonus_paid: u64,
+    pub referrer_total_bonus: u64,
+    pub remaining_reward_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SeasonOpenedEvent {
+    pub season_id: u32,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub reward_budget: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SeasonClosedEvent {
+    pub season_id: u32,
+    pub total_earned: u64,
+    pub participant_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LeaderboardPrizeTableUpdatedEvent {
+    pub season_id: u32,
+    pub prize_table: Vec<u64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LeaderboardPrizesDistributedEvent {
+    pub season_id: u32,
+    pub winners: Vec<Pubkey>,
+    pub prizes: Vec<u64>,
+    pub total: u64,
+    pub total_fees: u64,
+    pub remaining_reward_pool: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreakConfigUpdatedEvent {
+    pub reward_table: Vec<u64>,
+    pub freeze_price: u64,
+    pub max_freezes: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CheckInEvent {
+    pub player: Pubkey,
+    pub day: i64,
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub freezes_used: u8,
+    pub reward: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StreakFreezePurchasedEvent {
+    pub player: Pubkey,
+    pub quantity: u8,
+    pub price: u64,
+    pub streak_freezes: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub old_len: u64,
+    pub new_len: u64,
+    pub timestamp: i64,
+}
+
+// DISABLED: Old events - WRONG APPROACH
+// TokenMintedEvent is DISABLED because eat_energy_particle is disabled
+// TokensEarnedEvent is DISABLED because earn_tokens is disabled
+// Use AutoMintEvent and PlayerEarnedEvent instead
+
+// #[event]
+// pub struct TokenMintedEvent { ... } DISABLED
+
+// #[event]
+// pub struct TokensEarnedEvent { ... } DISABLED
+
+#[event]
+pub struct EmergencyPausedEvent {
+    pub owner: Pubkey,
+    pub pause_flags: u8,
+    pub reason_code: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnpausedEvent {
+    pub owner: Pubkey,
+    pub cleared_flags: u8,
+    pub pause_flags: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParameterChangeQueuedEvent {
+    pub index: u64,
+    pub change: ParameterChange,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParameterChangeExecutedEvent {
+    pub index: u64,
+    pub change: ParameterChange,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ParameterChangeCancelledEvent {
+    pub index: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GameServerUpdatedEvent {
+    pub previous: Pubkey,
+    pub game_server: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerTransferProposedEvent {
+    pub account: Pubkey,
+    pub current_owner: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerTransferAcceptedEvent {
+    pub account: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OwnerTransferCancelledEvent {
+    pub account: Pubkey,
+    pub owner: Pubkey,
+    pub cancelled_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MinterAddedEvent {
+    pub minter: Pubkey,
+    pub quota: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MinterRemovedEvent {
+    pub minter: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MinterQuotaUpdatedEvent {
+    pub minter: Pubkey,
+    pub quota: u64,
+    pub minted: u64,
+    pub timestamp: i64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGamePools<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + GameTokenPools::INIT_SPACE,
+        seeds = [b"game_pools"],
+        bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"game_pools_token_account"],
+        bump,
+        token::mint = game_token_mint,
+        token::authority = game_pools
+    )]
+    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Must be the program's own mint, created by initialize_game_mint
+    #[account(
+        seeds = [b"game_token_mint"],
+        bump
+    )]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+// Errors
+#[error_code]
+pub enum GameTokenError {
+    #[msg("Supply limit exceeded")]
+    SupplyLimitExceeded,
+    #[msg("Player rate limit exceeded")]
+    PlayerRateLimitExceeded,
+    #[msg("Insufficient pool balance")]
+    InsufficientPool,
+    #[msg("Signer is not authorized for this instruction")]
+    Unauthorized,
+    #[msg("Minter is already registered")]
+    MinterAlreadyExists,
+    #[msg("Minter is not registered")]
+    MinterNotFound,
+    #[msg("Maximum number of minters reached")]
+    TooManyMinters,
+    #[msg("Minter quota exceeded")]
+    MinterQuotaExceeded,
+    #[msg("Arithmetic overflow")]
+    MathOverflow,
+    #[msg("Game server key has not been set")]
+    GameServerNotSet,
+    #[msg("Earn voucher has expired")]
+    VoucherExpired,
+    #[msg("Earn voucher nonce already used")]
+    VoucherNonceUsed,
+    #[msg("Missing or invalid game server signature")]
+    InvalidVoucherSignature,
+    #[msg("Player hourly earn cap exceeded")]
+    PlayerHourlyCapExceeded,
+    #[msg("Player daily earn cap exceeded")]
+    PlayerDailyCapExceeded,
+    #[msg("Operation is paused")]
+    OperationPaused,
+    #[msg("Invalid pause flags")]
+    InvalidPauseFlags,
+    #[msg("Operation is not paused")]
+    NotPaused,
+    #[msg("Invalid new owner")]
+    InvalidNewOwner,
+    #[msg("No pending owner transfer")]
+    NoPendingOwner,
+    #[msg("Signer is not the pending owner")]
+    NotPendingOwner,
+    #[msg("Multisig owners must be unique and between 1 and the maximum")]
+    InvalidMultisigOwners,
+    #[msg("Multisig threshold must be between 1 and the number of owners")]
+    InvalidThreshold,
+    #[msg("Signer is not a multisig owner")]
+    NotMultisigOwner,
+    #[msg("Proposed transaction exceeds the maximum size")]
+    TransactionTooLarge,
+    #[msg("Multisig transaction has already been executed")]
+    TransactionAlreadyExecuted,
+    #[msg("Multisig owner set changed since this transaction was proposed")]
+    StaleTransaction,
+    #[msg("Not enough multisig approvals")]
+    NotEnoughApprovals,
+    #[msg("Invalid parameter change")]
+    InvalidParameterChange,
+    #[msg("Parameter change is not queued")]
+    ChangeNotQueued,
+    #[msg("Timelock delay has not passed yet")]
+    TimelockNotReady,
+    #[msg("Queued parameter change has expired")]
+    TimelockExpired,
+    #[msg("Split recipients must be unique, non-zero and sum to 10,000 bps")]
+    InvalidSplitConfig,
+    #[msg("Remaining accounts do not match the split recipients")]
+    SplitRecipientMismatch,
+    #[msg("Split config account is required for this change")]
+    SplitConfigRequired,
+    #[msg("Emission schedule is not configured")]
+    EmissionNotConfigured,
+    #[msg("Invalid pool transfer")]
+    InvalidPoolTransfer,
+    #[msg("Pool transfer limit exceeded")]
+    PoolTransferLimitExceeded,
+    #[msg("Invalid staking parameters")]
+    InvalidStakingParams,
+    #[msg("Stake amount must be greater than zero")]
+    InvalidStakeAmount,
+    #[msg("Insufficient staked balance")]
+    InsufficientStake,
+    #[msg("Nothing to withdraw")]
+    NothingToWithdraw,
+    #[msg("Unstake cooldown has not passed yet")]
+    UnstakeCooldownActive,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+    #[msg("Owner vesting account is required when the split includes the vesting escrow")]
+    VestingAccountRequired,
+    #[msg("Invalid earn budget configuration")]
+    InvalidEarnBudget,
+    #[msg("Global earn budget for this epoch is exhausted")]
+    EarnBudgetExhausted,
+    #[msg("Invalid rebalance configuration")]
+    InvalidRebalanceConfig,
+    #[msg("Active pool is above the low watermark")]
+    RebalanceNotNeeded,
+    #[msg("Transfer fees can only be harvested into the burn or active pool")]
+    InvalidFeeDestination,
+    #[msg("Token metadata requires the Token-2022 program")]
+    MetadataRequiresToken2022,
+    #[msg("Token name, symbol or URI is empty or too long")]
+    InvalidMintMetadata,
+    #[msg("Game token mint account is required for this change")]
+    MintAccountRequired,
+    #[msg("Max supply is locked and can only be lowered")]
+    SupplyCapLocked,
+    #[msg("Invalid session configuration")]
+    InvalidSessionConfig,
+    #[msg("Player already has an open session")]
+    SessionAlreadyOpen,
+    #[msg("Player has no open session")]
+    SessionNotOpen,
+    #[msg("Session has expired")]
+    SessionExpired,
+    #[msg("Session earn cap exceeded")]
+    SessionCapExceeded,
+    #[msg("Claim exceeds the player's unclaimed earnings")]
+    ClaimExceedsEarned,
+    #[msg("Invalid achievement id or expiry")]
+    InvalidAchievement,
+    #[msg("Achievement is not active")]
+    AchievementInactive,
+    #[msg("Achievement has expired")]
+    AchievementExpired,
+    #[msg("Achievement already unlocked by this player")]
+    AchievementAlreadyUnlocked,
+    #[msg("Invalid referral configuration")]
+    InvalidReferralConfig,
+    #[msg("Invalid or already registered referrer")]
+    InvalidReferrer,
+    #[msg("Referral accounts are required for players with a referrer")]
+    ReferralAccountsRequired,
+    #[msg("Referral accounts do not match the player's referrer")]
+    ReferralAccountMismatch,
+    #[msg("Invalid season id or time window")]
+    InvalidSeason,
+    #[msg("The current season must be closed first")]
+    SeasonStillOpen,
+    #[msg("Season is already closed")]
+    SeasonClosed,
+    #[msg("Season accounts are required while a season is open")]
+    SeasonAccountsRequired,
+    #[msg("Season reward budget exhausted")]
+    SeasonBudgetExhausted,
+    #[msg("Leaderboard account is required for this season")]
+    LeaderboardAccountRequired,
+    #[msg("Prize table is longer than the leaderboard")]
+    InvalidPrizeTable,
+    #[msg("Leaderboard prizes have already been distributed")]
+    PrizesAlreadyDistributed,
+    #[msg("Remaining accounts do not match the leaderboard winners")]
+    LeaderboardWinnerMismatch,
+    #[msg("Invalid streak configuration")]
+    InvalidStreakConfig,
+    #[msg("Player already checked in today")]
+    AlreadyCheckedIn,
+    #[msg("Streak freezes are not for sale")]
+    StreakFreezeUnavailable,
+    #[msg("Streak freeze limit reached")]
+    TooManyStreakFreezes,
+    #[msg("Manual minting is disabled while an emission schedule is active")]
+    EmissionScheduleActive,
+    #[msg("Too many unvested tranches in the vesting escrow")]
+    TooManyVestingTranches,
+    #[msg("Daily earn cap across sessions exceeded")]
+    SessionDailyCapExceeded,
+    #[msg("Too many pending unstake requests")]
+    TooManyUnstakeRequests,
+    #[msg("Owner share must go to the owner's associated token account")]
+    InvalidOwnerShareAccount,
+    #[msg("Owner share must go to the owner vesting escrow")]
+    OwnerShareMustVest,
+    #[msg("Account is not a migratable game token account")]
+    InvalidMigrationAccount,
+    #[msg("Account already has the current layout")]
+    AccountAlreadyMigrated,
+}
+
+// Context Structures
+#[derive(Accounts)]
+#[instruction(max_mints_per_player_per_minute: u8, is_infinite: bool, max_supply: u64)]
+pub struct InitializeMintingAuthority<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + MintingAuthority::INIT_SPACE,
+        seeds = [b"minting_authority"],
+        bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// DISABLED: EatEnergyParticle context - WRONG APPROACH
+// This context is DISABLED because eat_energy_particle function is disabled
+// Use auto_mint_tokens and player_earn_from_pool instead
+
+// #[derive(Accounts)]
+// pub struct EatEnergyParticle<'info> { ... } DISABLED
+
+// DISABLED: EarnTokens context - LEGACY APPROACH
+// This context is DISABLED - use PlayerEarnFromPool instead
+// PlayerEarnFromPool has proper player tracking and rate limiting
+
+// #[derive(Accounts)]
+// pub struct EarnTokens<'info> { ... } DISABLED
+
+#[derive(Accounts)]
+pub struct AutoMintTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools_token_account"],
+        bump
+    )]
+    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Recipients can only change through the timelock, not per mint call
+    #[account(
+        seeds = [b"split_config"],
+        bump = split_config.bump
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+
+    #[account(
+        mut,
+        address = game_pools.game_token_mint
+    )]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    // Required when the split config includes the owner vesting escrow
+    #[account(
+        mut,
+        seeds = [b"owner_vesting"],
+        bump = owner_vesting.bump
+    )]
+    pub owner_vesting: Option<Account<'info, VestingEscrow>>,
+
+    // Owner or a registered minter, checked against quotas in the handler
+    pub minter: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Accounts)]
+pub struct CrankEmission<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        seeds = [b"game_pools_token_account"],
+        bump
+    )]
+    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        seeds = [b"split_config"],
+        bump = split_config.bump
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+
+    #[account(
+        mut,
+        address = game_pools.game_token_mint
+    )]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    // Required when the split config includes the owner vesting escrow
+    #[account(
+        mut,
+        seeds = [b"owner_vesting"],
+        bump = owner_vesting.bump
+    )]
+    pub owner_vesting: Option<Account<'info, VestingEscrow>>,
+
+    // Anyone can crank
+    pub cranker: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct TransferOwnership<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.owner == owner.key() @ GameTokenError::Unauthorized
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptOwner<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.pending_owner == new_owner.key() @ GameTokenError::NotPendingOwner
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    pub new_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QueueParameterChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.owner == owner.key() @ GameTokenError::Unauthorized
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + QueuedParameterChange::INIT_SPACE,
+        seeds = [b"timelock", &authority.timelock_count.to_le_bytes()],
+        bump
+    )]
+    pub queued_change: Account<'info, QueuedParameterChange>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteParameterChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"timelock", &queued_change.index.to_le_bytes()],
+        bump = queued_change.bump
+    )]
+    pub queued_change: Account<'info, QueuedParameterChange>,
+
+    // Only needed for MintSplit changes
+    #[account(
+        mut,
+        seeds = [b"split_config"],
+        bump = split_config.bump
+    )]
+    pub split_config: Option<Account<'info, SplitConfig>>,
+
+    // Required for SupplyCap and MintSplit changes. MintSplit changes also pass the
+    // new recipients' token accounts in remaining_accounts, in config order
+    #[account(
+        seeds = [b"game_token_mint"],
+        bump,
+        mint::authority = authority
+    )]
+    pub game_token_mint: Option<InterfaceAccount<'info, Mint>>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeSplitConfig<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.owner == owner.key() @ GameTokenError::Unauthorized
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + SplitConfig::INIT_SPACE,
+        seeds = [b"split_config"],
+        bump
+    )]
+    pub split_config: Account<'info, SplitConfig>,
+
+    #[account(
+        seeds = [b"game_token_mint"],
+        bump,
+        mint::authority = authority
+    )]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeOwnerVesting<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.owner == owner.key() @ GameTokenError::Unauthorized
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VestingEscrow::INIT_SPACE,
+        seeds = [b"owner_vesting"],
+        bump
+    )]
+    pub owner_vesting: Account<'info, VestingEscrow>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"owner_vesting_token_account"],
+        bump,
+        token::mint = game_token_mint,
+        token::authority = owner_vesting,
+        token::token_program = token_program
+    )]
+    pub owner_vesting_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseVested<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"owner_vesting"],
+        bump = owner_vesting.bump
+    )]
+    pub owner_vesting: Account<'info, VestingEscrow>,
+
+    #[account(
+        mut,
+        address = owner_vesting.token_account
+    )]
+    pub owner_vesting_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Vested tokens can only go to an account of the current owner
+    #[account(
+        mut,
+        token::mint = game_token_mint,
+        token::authority = authority.owner
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct CancelParameterChange<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.owner == owner.key() @ GameTokenError::Unauthorized
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"timelock", &queued_change.index.to_le_bytes()],
+        bump = queued_change.bump
+    )]
+    pub queued_change: Account<'info, QueuedParameterChange>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageMinters<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.owner == owner.key() @ GameTokenError::Unauthorized
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerEarnFromPool<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerMintStats::INIT_SPACE,
+        seeds = [b"player_stats", player.key().as_ref()],
+        bump
+    )]
+    pub player_stats: Account<'info, PlayerMintStats>,
+
+    #[account(
+        mut,
+        seeds = [b"player_session", player.key().as_ref()],
+        bump = player_session.bump
+    )]
+    pub player_session: Account<'info, PlayerSession>,
+
+    // Required while a season is open
+    #[account(
+        mut,
+        seeds = [b"season".as_ref(), &game_pools.current_season_id.to_le_bytes()],
+        bump = season.bump
+    )]
+    pub season: Option<Account<'info, Season>>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerSeasonStats::INIT_SPACE,
+        seeds = [
+            b"player_season".as_ref(),
+            &game_pools.current_season_id.to_le_bytes(),
+            player.key().as_ref()
+        ],
+        bump
+    )]
+    pub player_season_stats: Option<Account<'info, PlayerSeasonStats>>,
+
+    // Required while the open season has a leaderboard
+    #[account(
+        mut,
+        seeds = [b"leaderboard".as_ref(), &game_pools.current_season_id.to_le_bytes()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Option<Account<'info, Leaderboard>>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to find the ed25519 voucher signature
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub referral_bonus: ReferralBonus<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub clock: Sysvar<'info, Clock>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PlayerClaimTokens<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools_token_account"],
+        bump
+    )]
+    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"player_stats", player.key().as_ref()],
+        bump = player_stats.bump
+    )]
+    pub player_stats: Account<'info, PlayerMintStats>,
+
+    #[account(
+        mut,
+        token::mint = game_token_mint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub player: Signer<'info>,
+
+    pub referral_bonus: ReferralBonus<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyControl<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.owner == owner.key()
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageGamePools<'info> {
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptPoolsOwner<'info> {
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.pending_authority == new_owner.key() @ GameTokenError::NotPendingOwner
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    pub new_owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(create_key: Pubkey)]
+pub struct CreateMultisig<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Multisig::INIT_SPACE,
+        seeds = [b"multisig", create_key.as_ref()],
+        bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// CHECK: PDA that signs executed multisig transactions, holds no data
+    #[account(
+        seeds = [b"multisig_signer", multisig.key().as_ref()],
+        bump
+    )]
+    pub multisig_signer: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeTransaction<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + MultisigTransaction::INIT_SPACE,
+        seeds = [
+            b"multisig_tx",
+            multisig.key().as_ref(),
+            &multisig.transaction_count.to_le_bytes()
+        ],
+        bump
+    )]
+    pub transaction: Account<'info, MultisigTransaction>,
+
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveTransaction<'info> {
+    #[account(
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"multisig_tx",
+            multisig.key().as_ref(),
+            &transaction.index.to_le_bytes()
+        ],
+        bump = transaction.bump
+    )]
+    pub transaction: Account<'info, MultisigTransaction>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteTransaction<'info> {
+    // Not mut: the executed instruction may itself update the multisig
+    #[account(
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    /// CHECK: PDA that signs the executed instruction, holds no data
+    #[account(
+        seeds = [b"multisig_signer", multisig.key().as_ref()],
+        bump = multisig.signer_bump
+    )]
+    pub multisig_signer: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"multisig_tx",
+            multisig.key().as_ref(),
+            &transaction.index.to_le_bytes()
+        ],
+        bump = transaction.bump
+    )]
+    pub transaction: Account<'info, MultisigTransaction>,
+
+    pub executor: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MultisigAuth<'info> {
+    #[account(
+        mut,
+        seeds = [b"multisig", multisig.create_key.as_ref()],
+        bump = multisig.bump
+    )]
+    pub multisig: Account<'info, Multisig>,
+
+    #[account(
+        seeds = [b"multisig_signer", multisig.key().as_ref()],
+        bump = multisig.signer_bump
+    )]
+    pub multisig_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePoolTokenAccounts<'info> {
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"reward_pool_token_account"],
+        bump,
+        token::mint = game_token_mint,
+        token::authority = game_pools,
+        token::token_program = token_program
+    )]
+    pub reward_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"reserve_pool_token_account"],
+        bump,
+        token::mint = game_token_mint,
+        token::authority = game_pools,
+        token::token_program = token_program
+    )]
+    pub reserve_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"burn_pool_token_account"],
+        bump,
+        token::mint = game_token_mint,
+        token::authority = game_pools,
+        token::token_program = token_program
+    )]
+    pub burn_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferBetweenPools<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = minting_authority.bump
+    )]
+    pub minting_authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools_token_account"],
+        bump
+    )]
+    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool_token_account"],
+        bump
+    )]
+    pub reward_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"reserve_pool_token_account"],
+        bump
+    )]
+    pub reserve_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"burn_pool_token_account"],
+        bump
+    )]
+    pub burn_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> TransferBetweenPools<'info> {
+    pub fn pool_token_account(&self, kind: PoolKind) -> AccountInfo<'info> {
+        match kind {
+            PoolKind::Active => self.game_pools_token_account.to_account_info(),
+            PoolKind::Reward => self.reward_pool_token_account.to_account_info(),
+            PoolKind::Reserve => self.reserve_pool_token_account.to_account_info(),
+            PoolKind::Burn => self.burn_pool_token_account.to_account_info(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct BurnFromBurnPool<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = minting_authority.bump
+    )]
+    pub minting_authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"burn_pool_token_account"],
+        bump
+    )]
+    pub burn_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = game_pools.game_token_mint
+    )]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeStaking<'info> {
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + StakePool::INIT_SPACE,
+        seeds = [b"stake_pool"],
+        bump
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        init,
+        payer = payer,
+        seeds = [b"stake_vault"],
+        bump,
+        token::mint = game_token_mint,
+        token::authority = stake_pool,
+        token::token_program = token_program
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetStakingParams<'info> {
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_pool"],
+        bump = stake_pool.bump
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Stake<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_pool"],
+        bump = stake_pool.bump
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump = stake_pool.vault_bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerStake::INIT_SPACE,
+        seeds = [b"player_stake", player.key().as_ref()],
+        bump
+    )]
+    pub player_stake: Account<'info, PlayerStake>,
+
+    #[account(
+        mut,
+        token::mint = game_token_mint,
+        token::authority = player
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_pool"],
+        bump = stake_pool.bump
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault"],
+        bump = stake_pool.vault_bump
+    )]
+    pub stake_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"player_stake", player.key().as_ref()],
+        bump = player_stake.bump
+    )]
+    pub player_stake: Account<'info, PlayerStake>,
+
+    #[account(
+        mut,
+        token::mint = game_token_mint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub player: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimStakeRewards<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool_token_account"],
+        bump
+    )]
+    pub reward_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_pool"],
+        bump = stake_pool.bump
+    )]
+    pub stake_pool: Account<'info, StakePool>,
+
+    #[account(
+        mut,
+        seeds = [b"player_stake", player.key().as_ref()],
+        bump = player_stake.bump
+    )]
+    pub player_stake: Account<'info, PlayerStake>,
+
+    #[account(
+        mut,
+        token::mint = game_token_mint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub player: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct RebalancePools<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools_token_account"],
+        bump
+    )]
+    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"reserve_pool_token_account"],
+        bump
+    )]
+    pub reserve_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    // Anyone can trigger a rebalance
+    pub caller: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct HarvestTransferFees<'info> {
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools_token_account"],
+        bump
+    )]
+    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"burn_pool_token_account"],
+        bump
+    )]
+    pub burn_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = game_pools.game_token_mint
+    )]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token2022>,
+}
+
+impl<'info> HarvestTransferFees<'info> {
+    pub fn fee_destination(
+        &mut self,
+        kind: PoolKind,
+    ) -> Result<&mut InterfaceAccount<'info, TokenAccount>> {
+        match kind {
+            PoolKind::Active => Ok(&mut self.game_pools_token_account),
+            PoolKind::Burn => Ok(&mut self.burn_pool_token_account),
+            _ => err!(GameTokenError::InvalidFeeDestination),
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct InitializeGame<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + MintingAuthority::INIT_SPACE,
+        seeds = [b"minting_authority"],
+        bump
+    )]
+    pub authority: Box<Account<'info, MintingAuthority>>,
+
+    /// CHECK: created and initialized as a mint by this instruction
+    #[account(
+        mut,
+        seeds = [b"game_token_mint"],
+        bump
+    )]
+    pub game_token_mint: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + GameTokenPools::INIT_SPACE,
+        seeds = [b"game_pools"],
+        bump
+    )]
+    pub game_pools: Box<Account<'info, GameTokenPools>>,
+
+    /// CHECK: created and initialized as a token account by this instruction
+    #[account(
+        mut,
+        seeds = [b"game_pools_token_account"],
+        bump
+    )]
+    pub game_pools_token_account: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeGameMint<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.owner == owner.key() @ GameTokenError::Unauthorized
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    /// CHECK: created and initialized as a mint by this instruction
+    #[account(
+        mut,
+        seeds = [b"game_token_mint"],
+        bump
+    )]
+    pub game_token_mint: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct LockMaxSupply<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump,
+        constraint = authority.owner == owner.key() @ GameTokenError::Unauthorized
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReconcileSupply<'info> {
+    #[account(
+        mut,
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        seeds = [b"game_token_mint"],
+        bump,
+        mint::authority = authority
+    )]
+    pub game_token_mint: InterfaceAccount<'info, Mint>,
+
+    // Anyone can reconcile
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct StartSession<'info> {
     #[account(
-        init,
-        payer = payer,
-        space = 8 + GameTokenPools::INIT_SPACE,
         seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerMintStats::INIT_SPACE,
+        seeds = [b"player_stats", player.key().as_ref()],
+        bump
+    )]
+    pub player_stats: Account<'info, PlayerMintStats>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerSession::INIT_SPACE,
+        seeds = [b"player_session", player.key().as_ref()],
         bump
     )]
+    pub player_session: Account<'info, PlayerSession>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct EndSession<'info> {
+    #[account(
+        mut,
+        seeds = [b"player_stats", player.key().as_ref()],
+        bump = player_stats.bump
+    )]
+    pub player_stats: Account<'info, PlayerMintStats>,
+
+    #[account(
+        mut,
+        seeds = [b"player_session", player.key().as_ref()],
+        bump = player_session.bump
+    )]
+    pub player_session: Account<'info, PlayerSession>,
+
+    pub player: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(achievement_id: u16)]
+pub struct CreateAchievement<'info> {
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
     pub game_pools: Account<'info, GameTokenPools>,
 
     #[account(
         init,
         payer = payer,
-        seeds = [b"game_pools_token_account"],
-        bump,
-        token::mint = game_token_mint,
-        token::authority = game_pools
+        space = 8 + Achievement::INIT_SPACE,
+        seeds = [b"achievement".as_ref(), &achievement_id.to_le_bytes()],
+        bump
     )]
-    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub achievement: Account<'info, Achievement>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateAchievement<'info> {
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"achievement".as_ref(), &achievement.id.to_le_bytes()],
+        bump = achievement.bump
+    )]
+    pub achievement: Account<'info, Achievement>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockAchievement<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool_token_account"],
+        bump
+    )]
+    pub reward_pool_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(
+        mut,
+        seeds = [b"achievement".as_ref(), &achievement.id.to_le_bytes()],
+        bump = achievement.bump
+    )]
+    pub achievement: Account<'info, Achievement>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerAchievements::INIT_SPACE,
+        seeds = [b"player_achievements", player.key().as_ref()],
+        bump
+    )]
+    pub player_achievements: Account<'info, PlayerAchievements>,
+
+    #[account(
+        mut,
+        token::mint = game_token_mint
+    )]
+    pub player_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = game_pools.game_token_mint)]
     pub game_token_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
+    pub player: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, used to find the ed25519 attestation
+    #[account(address = INSTRUCTIONS_SYSVAR_ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(referrer: Pubkey)]
+pub struct RegisterReferrer<'info> {
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + PlayerMintStats::INIT_SPACE,
+        seeds = [b"player_stats", player.key().as_ref()],
+        bump
+    )]
+    pub player_stats: Account<'info, PlayerMintStats>,
+
+    #[account(
+        init,
+        payer = player,
+        space = 8 + Referral::INIT_SPACE,
+        seeds = [b"referral", player.key().as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, Referral>,
+
+    #[account(
+        init_if_needed,
+        payer = player,
+        space = 8 + ReferrerStats::INIT_SPACE,
+        seeds = [b"referrer_stats", referrer.as_ref()],
+        bump
+    )]
+    pub referrer_stats: Account<'info, ReferrerStats>,
+
+    #[account(mut)]
+    pub player: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Accounts used to pay a referral bonus; only required when the player has a referrer
+#[derive(Accounts)]
+pub struct ReferralBonus<'info> {
+    #[account(
+        mut,
+        seeds = [b"referral", referral.player.as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Option<Account<'info, Referral>>,
+
+    #[account(
+        mut,
+        seeds = [b"referrer_stats", referrer_stats.referrer.as_ref()],
+        bump = referrer_stats.bump
+    )]
+    pub referrer_stats: Option<Account<'info, ReferrerStats>>,
+
+    #[account(mut)]
+    pub referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"reward_pool_token_account"],
+        bump
+    )]
+    pub reward_pool_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+impl<'info> ReferralBonus<'info> {
+    // Pays referral_bonus_bps of `amount` to the player's referrer from the reward pool,
+    // limited by the per-referee and per-referrer caps and the pool balance
+    pub fn pay(
+        &mut self,
+        game_pools: &mut Account<'info, GameTokenPools>,
+        player_stats: &PlayerMintStats,
+        game_token_mint: &InterfaceAccount<'info, Mint>,
+        token_program: &Interface<'info, TokenInterface>,
+        amount: u64,
+        current_time: i64,
+    ) -> Result<u64> {
+        if player_stats.referrer == Pubkey::default() || game_pools.referral_bonus_bps == 0 {
+            return Ok(0);
+        }
+
+        let missing = GameTokenError::ReferralAccountsRequired;
+        let referral = self.referral.as_mut().ok_or(missing)?;
+        let referrer_stats = self.referrer_stats.as_mut().ok_or(missing)?;
+        let referrer_token_account = self.referrer_token_account.as_ref().ok_or(missing)?;
+        let reward_pool_token_account = self.reward_pool_token_account.as_ref().ok_or(missing)?;
+        require!(
+            referral.player == player_stats.player
+                && referral.referrer == player_stats.referrer
+                && referrer_stats.referrer == player_stats.referrer
+                && referrer_token_account.owner == player_stats.referrer
+                && referrer_token_account.mint == game_pools.game_token_mint,
+            GameTokenError::ReferralAccountMismatch
+        );
+
+        let mut bonus = (amount as u128 * game_pools.referral_bonus_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        if game_pools.max_referral_bonus_per_referee > 0 {
+            bonus = bonus.min(
+                game_pools
+                    .max_referral_bonus_per_referee
+                    .saturating_sub(referral.bonus_paid),
+            );
+        }
+        if game_pools.max_referral_bonus_per_referrer > 0 {
+            bonus = bonus.min(
+                game_pools
+                    .max_referral_bonus_per_referrer
+                    .saturating_sub(referrer_stats.total_bonus_paid),
+            );
+        }
+        bonus = bonus.min(game_pools.reward_pool);
+        if bonus == 0 {
+            return Ok(0);
+        }
+        let fee = transfer_fee_for(game_token_mint, bonus)?;
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: reward_pool_token_account.to_account_info(),
+                    to: referrer_token_account.to_account_info(),
+                    authority: game_pools.to_account_info(),
+                    mint: game_token_mint.to_account_info(),
+                },
+                &[&[
+                    b"game_pools",
+                    &[game_pools.bump]
+                ]]
+            ),
+            bonus,
+            game_token_mint.decimals,
+        )?;
+
+        game_pools.reward_pool -= bonus;
+        referral.bonus_paid = referral
+            .bonus_paid
+            .checked_add(bonus)
+            .ok_or(GameTokenError::MathOverflow)?;
+        referrer_stats.total_bonus_paid = referrer_stats
+            .total_bonus_paid
+            .checked_add(bonus)
+            .ok_or(GameTokenError::MathOverflow)?;
+
+        emit!(ReferralBonusPaidEvent {
+            referrer: referral.referrer,
+            referee: referral.player,
+            source_amount: amount,
+            bonus,
+            fee,
+            referee_bonus_paid: referral.bonus_paid,
+            referrer_total_bonus: referrer_stats.total_bonus_paid,
+            remaining_reward_pool: game_pools.reward_pool,
+            timestamp: current_time,
+        });
+
+        Ok(bonus)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(season_id: u32)]
+pub struct OpenSeason<'info> {
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Season::INIT_SPACE,
+        seeds = [b"season".as_ref(), &season_id.to_le_bytes()],
+        bump
+    )]
+    pub season: Account<'info, Season>,
+
     pub authority: Signer<'info>,
 
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
-// Errors
-#[error_code]
-pub enum GameTokenError {
-    #[msg("Supply limit exceeded")]
-    SupplyLimitExceeded,
-    #[msg("Player rate limit exceeded")]
-    PlayerRateLimitExceeded,
-    #[msg("Insufficient pool balance")]
-    InsufficientPool,
+#[derive(Accounts)]
+pub struct CloseSeason<'info> {
+    #[account(
+        mut,
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"season".as_ref(), &game_pools.current_season_id.to_le_bytes()],
+        bump = season.bump
+    )]
+    pub season: Account<'info, Season>,
+
+    pub authority: Signer<'info>,
 }
 
-// Context Structures
 #[derive(Accounts)]
-#[instruction(max_mints_per_player_per_minute: u8, is_infinite: bool, max_supply: u64)]
-pub struct InitializeMintingAuthority<'info> {
+pub struct InitializeLeaderboard<'info> {
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        mut,
+        seeds = [b"season".as_ref(), &game_pools.current_season_id.to_le_bytes()],
+        bump = season.bump
+    )]
+    pub season: Account<'info, Season>,
+
     #[account(
         init,
-        payer = owner,
-        space = 8 + MintingAuthority::INIT_SPACE,
-        seeds = [b"minting_authority"],
+        payer = payer,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [b"leaderboard".as_ref(), &season.id.to_le_bytes()],
         bump
     )]
-    pub authority: Account<'info, MintingAuthority>,
+    pub leaderboard: Account<'info, Leaderboard>,
+
+    pub authority: Signer<'info>,
 
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
-// DISABLED: EatEnergyParticle context - WRONG APPROACH
-// This context is DISABLED because eat_energy_particle function is disabled
-// Use auto_mint_tokens and player_earn_from_pool instead
-
-// #[derive(Accounts)]
-// pub struct EatEnergyParticle<'info> { ... } DISABLED
+#[derive(Accounts)]
+pub struct SetLeaderboardPrizes<'info> {
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
 
-// DISABLED: EarnTokens context - LEGACY APPROACH
-// This context is DISABLED - use PlayerEarnFromPool instead
-// PlayerEarnFromPool has proper player tracking and rate limiting
+    #[account(
+        mut,
+        seeds = [b"leaderboard".as_ref(), &leaderboard.season_id.to_le_bytes()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
 
-// #[derive(Accounts)]
-// pub struct EarnTokens<'info> { ... } DISABLED
+    pub authority: Signer<'info>,
+}
 
 #[derive(Accounts)]
-pub struct AutoMintTokens<'info> {
+pub struct DistributeLeaderboardPrizes<'info> {
     #[account(
-        mut,
         seeds = [b"minting_authority"],
         bump = authority.bump
     )]
@@ -422,25 +6727,67 @@ pub struct AutoMintTokens<'info> {
 
     #[account(
         mut,
-        seeds = [b"game_pools_token_account"],
+        seeds = [b"reward_pool_token_account"],
         bump
     )]
-    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub reward_pool_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    #[account(mut)]
-    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"season".as_ref(), &leaderboard.season_id.to_le_bytes()],
+        bump = season.bump
+    )]
+    pub season: Account<'info, Season>,
+
+    #[account(
+        mut,
+        seeds = [b"leaderboard".as_ref(), &leaderboard.season_id.to_le_bytes()],
+        bump = leaderboard.bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
 
+    #[account(address = game_pools.game_token_mint)]
     pub game_token_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(mut)]
-    pub owner: Signer<'info>,
+    // Anyone can trigger the payout once the season is closed
+    pub caller: Signer<'info>,
 
     pub token_program: Interface<'info, TokenInterface>,
-    pub clock: Sysvar<'info, Clock>,
 }
 
 #[derive(Accounts)]
-pub struct PlayerEarnFromPool<'info> {
+pub struct SetStreakConfig<'info> {
+    #[account(
+        seeds = [b"game_pools"],
+        bump = game_pools.bump,
+        constraint = game_pools.authority == authority.key() @ GameTokenError::Unauthorized
+    )]
+    pub game_pools: Account<'info, GameTokenPools>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + StreakConfig::INIT_SPACE,
+        seeds = [b"streak_config"],
+        bump
+    )]
+    pub streak_config: Account<'info, StreakConfig>,
+
+    pub authority: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckIn<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
     #[account(
         mut,
         seeds = [b"game_pools"],
@@ -450,13 +6797,19 @@ pub struct PlayerEarnFromPool<'info> {
 
     #[account(
         mut,
-        seeds = [b"game_pools_token_account"],
+        seeds = [b"reward_pool_token_account"],
         bump
     )]
-    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub reward_pool_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        init,
+        seeds = [b"streak_config"],
+        bump = streak_config.bump
+    )]
+    pub streak_config: Account<'info, StreakConfig>,
+
+    #[account(
+        init_if_needed,
         payer = player,
         space = 8 + PlayerMintStats::INIT_SPACE,
         seeds = [b"player_stats", player.key().as_ref()],
@@ -464,21 +6817,30 @@ pub struct PlayerEarnFromPool<'info> {
     )]
     pub player_stats: Account<'info, PlayerMintStats>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        token::mint = game_token_mint
+    )]
     pub player_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(address = game_pools.game_token_mint)]
     pub game_token_mint: InterfaceAccount<'info, Mint>,
 
     #[account(mut)]
     pub player: Signer<'info>,
 
     pub token_program: Interface<'info, TokenInterface>,
-    pub clock: Sysvar<'info, Clock>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct PlayerClaimTokens<'info> {
+pub struct BuyStreakFreeze<'info> {
+    #[account(
+        seeds = [b"minting_authority"],
+        bump = authority.bump
+    )]
+    pub authority: Account<'info, MintingAuthority>,
+
     #[account(
         mut,
         seeds = [b"game_pools"],
@@ -488,42 +6850,712 @@ pub struct PlayerClaimTokens<'info> {
 
     #[account(
         mut,
-        seeds = [b"game_pools_token_account"],
+        seeds = [b"reward_pool_token_account"],
         bump
     )]
-    pub game_pools_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub reward_pool_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-        init_if_needed,
-        payer = player,
-        space = 8 + PlayerMintStats::INIT_SPACE,
+        seeds = [b"streak_config"],
+        bump = streak_config.bump
+    )]
+    pub streak_config: Account<'info, StreakConfig>,
+
+    #[account(
+        mut,
         seeds = [b"player_stats", player.key().as_ref()],
-        bump
+        bump = player_stats.bump
     )]
     pub player_stats: Account<'info, PlayerMintStats>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        token::mint = game_token_mint,
+        token::authority = player
+    )]
     pub player_token_account: InterfaceAccount<'info, TokenAccount>,
 
+    #[account(address = game_pools.game_token_mint)]
     pub game_token_mint: InterfaceAccount<'info, Mint>,
 
-    #[account(mut)]
     pub player: Signer<'info>,
 
     pub token_program: Interface<'info, TokenInterface>,
-    pub clock: Sysvar<'info, Clock>,
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct EmergencyControl<'info> {
-    #[account(
-        mut,
-        seeds = [b"minting_authority"],
-        bump = authority.bump,
-        constraint = authority.owner == owner.key()
-    )]
-    pub authority: Account<'info, MintingAuthority>,
+pub struct MigrateAccount<'info> {
+    /// CHECK: still in an older layout, the discriminator is checked by the handler
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
 
-    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::instruction::{BorrowedAccountMeta, BorrowedInstruction};
+    use anchor_lang::solana_program::program_pack::Pack;
+
+    fn key(seed: u8) -> Pubkey {
+        Pubkey::new_from_array([seed; 32])
+    }
+
+    fn assert_error<T: std::fmt::Debug>(result: Result<T>, expected: GameTokenError) {
+        match result {
+            Err(Error::AnchorError(error)) => {
+                assert_eq!(error.error_code_number, u32::from(expected), "{}", error.error_msg)
+            }
+            other => panic!("expected {:?}, got {:?}", expected, other),
+        }
+    }
+
+    // Split
+
+    fn split_config(weights: &[u16], dust_recipient: u8) -> SplitConfig {
+        SplitConfig {
+            recipients: weights
+                .iter()
+                .enumerate()
+                .map(|(i, &weight_bps)| SplitRecipient {
+                    token_account: key(i as u8 + 1),
+                    weight_bps,
+                })
+                .collect(),
+            dust_recipient,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn compute_shares_sends_rounding_dust_to_dust_recipient() {
+        let config = split_config(&[6_000, 2_000, 1_500, 500], 1);
+        let shares = config.compute_shares(10_003).unwrap();
+        assert_eq!(shares, vec![6_001, 2_000 + 2, 1_500, 500]);
+        assert_eq!(shares.iter().sum::<u64>(), 10_003);
+        assert_eq!(config.share_of(&shares, &key(2)), 2_002);
+    }
+
+    #[test]
+    fn compute_shares_does_not_overflow_on_large_amounts() {
+        let config = split_config(&[8_000, 2_000], 0);
+        let shares = config.compute_shares(u64::MAX).unwrap();
+        assert_eq!(shares.iter().map(|share| *share as u128).sum::<u128>(), u64::MAX as u128);
+    }
+
+    #[test]
+    fn validate_split_rejects_bad_configs() {
+        let valid = split_config(&[5_000, 5_000], 0);
+        assert!(validate_split(&valid.recipients, 0).is_ok());
+        assert_error(validate_split(&valid.recipients, 2), GameTokenError::InvalidSplitConfig);
+        let short = split_config(&[5_000, 4_999], 0);
+        assert_error(validate_split(&short.recipients, 0), GameTokenError::InvalidSplitConfig);
+        let mut duplicate = valid.recipients.clone();
+        duplicate[1].token_account = duplicate[0].token_account;
+        assert_error(validate_split(&duplicate, 0), GameTokenError::InvalidSplitConfig);
+    }
+
+    // Runs check_split_recipient_account on a classic SPL token account at `address`
+    fn check_recipient(authority: &MintingAuthority, address: Pubkey, holder: Pubkey, mint: Pubkey) -> Result<()> {
+        let mut data = vec![0; spl_token_2022::state::Account::LEN];
+        spl_token_2022::state::Account {
+            mint,
+            owner: holder,
+            state: spl_token_2022::state::AccountState::Initialized,
+            ..Default::default()
+        }
+        .pack_into_slice(&mut data);
+
+        let mut lamports = 0;
+        let token_program = anchor_spl::token::ID;
+        let info = AccountInfo::new(&address, false, true, &mut lamports, &mut data, &token_program, false, 0);
+        check_split_recipient_account(authority, &key(9), &token_program, &info)
+    }
+
+    #[test]
+    fn owner_share_only_goes_to_owner_ata() {
+        let authority = MintingAuthority {
+            owner: key(7),
+            ..Default::default()
+        };
+        let owner_ata = get_associated_token_address_with_program_id(&key(7), &key(9), &anchor_spl::token::ID);
+
+        check_recipient(&authority, owner_ata, key(7), key(9)).unwrap();
+        check_recipient(&authority, key(1), key(8), key(9)).unwrap();
+        assert_error(
+            check_recipient(&authority, key(1), key(7), key(9)),
+            GameTokenError::InvalidOwnerShareAccount,
+        );
+        assert_error(
+            check_recipient(&authority, key(1), key(8), key(10)),
+            GameTokenError::SplitRecipientMismatch,
+        );
+
+        // With vesting set up, even the owner's ATA is rejected
+        let vesting = MintingAuthority {
+            owner_vesting_token_account: key(3),
+            ..authority
+        };
+        check_recipient(&vesting, key(1), key(8), key(9)).unwrap();
+        assert_error(
+            check_recipient(&vesting, owner_ata, key(7), key(9)),
+            GameTokenError::OwnerShareMustVest,
+        );
+    }
+
+    // Migration
+
+    // Baseline layouts: MintingAuthority {owner, total_minted, is_infinite, max_supply,
+    // max_mints_per_player_per_minute, bump}, GameTokenPools {authority, active_pool,
+    // reward_pool, reserve_pool, burn_pool, game_token_mint, bump}
+    #[test]
+    fn upgrade_legacy_minting_authority_keeps_fields_and_sets_defaults() {
+        let mut legacy = MintingAuthority::DISCRIMINATOR.to_vec();
+        legacy.extend_from_slice(key(7).as_ref());
+        legacy.extend_from_slice(&500u64.to_le_bytes());
+        legacy.push(0);
+        legacy.extend_from_slice(&1_000u64.to_le_bytes());
+        legacy.extend_from_slice(&[3, 254]);
+
+        let upgraded = upgrade_legacy_account_data(&legacy).unwrap();
+        assert_eq!(upgraded.len(), 8 + MintingAuthority::INIT_SPACE);
+        let authority = MintingAuthority::try_deserialize(&mut &upgraded[..]).unwrap();
+        assert_eq!(authority.owner, key(7));
+        assert_eq!(authority.total_minted, 500);
+        assert_eq!(authority.max_supply, 1_000);
+        assert_eq!(authority.max_mints_per_player_per_minute, 3);
+        assert_eq!(authority.bump, 254);
+        assert!(authority.minters.is_empty());
+        assert_eq!(authority.emission_decay_bps, BPS_DENOMINATOR as u16);
+        assert_eq!(authority.emission_decay_interval, 1);
+        assert!(!authority.emission_enabled());
+
+        assert_error(upgrade_legacy_account_data(&upgraded), GameTokenError::AccountAlreadyMigrated);
+    }
+
+    #[test]
+    fn upgrade_legacy_game_pools_and_rejects_other_accounts() {
+        let mut legacy = GameTokenPools::DISCRIMINATOR.to_vec();
+        legacy.extend_from_slice(key(1).as_ref());
+        for pool in [10u64, 20, 30, 40] {
+            legacy.extend_from_slice(&pool.to_le_bytes());
+        }
+        legacy.extend_from_slice(key(2).as_ref());
+        legacy.push(253);
+
+        let upgraded = upgrade_legacy_account_data(&legacy).unwrap();
+        let game_pools = GameTokenPools::try_deserialize(&mut &upgraded[..]).unwrap();
+        assert_eq!(game_pools.authority, key(1));
+        assert_eq!(game_pools.burn_pool, 40);
+        assert_eq!(game_pools.game_token_mint, key(2));
+        assert_eq!(game_pools.bump, 253);
+        assert_eq!(game_pools.earn_epoch_length, 86_400);
+
+        let mut stake = PlayerStake::DISCRIMINATOR.to_vec();
+        stake.resize(64, 0);
+        assert_error(upgrade_legacy_account_data(&stake), GameTokenError::InvalidMigrationAccount);
+    }
+
+    // Emission
+
+    fn emission_schedule(initial_per_epoch: u64, decay_bps: u16, decay_interval: u32) -> MintingAuthority {
+        MintingAuthority {
+            emission_epoch_length: 3_600,
+            emission_initial_per_epoch: initial_per_epoch,
+            emission_decay_bps: decay_bps,
+            emission_decay_interval: decay_interval,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn emission_for_epoch_decays_per_interval() {
+        let authority = emission_schedule(1_000, 5_000, 2);
+        assert_eq!(authority.emission_for_epoch(0), 1_000);
+        assert_eq!(authority.emission_for_epoch(1), 1_000);
+        assert_eq!(authority.emission_for_epoch(2), 500);
+        assert_eq!(authority.emission_for_epoch(7), 125);
+    }
+
+    #[test]
+    fn emission_for_epoch_handles_old_schedules() {
+        let flat = emission_schedule(1_000, BPS_DENOMINATOR as u16, 1);
+        assert_eq!(flat.emission_for_epoch(u64::MAX), 1_000);
+
+        // Two years of hourly epochs with a slow decay
+        let slow = emission_schedule(1_000_000_000, 9_999, 1);
+        let expected = 1_000_000_000f64 * 0.9999f64.powi(17_520);
+        let actual = slow.emission_for_epoch(17_520) as f64;
+        assert!((actual - expected).abs() < 10.0, "{} vs {}", actual, expected);
+        assert_eq!(slow.emission_for_epoch(u64::MAX), 0);
+    }
+
+    #[test]
+    fn emission_due_is_linear_within_epochs() {
+        let mut authority = emission_schedule(3_600, 5_000, 1);
+        assert_eq!(authority.emission_due(1_800).unwrap(), (1_800, 1_800));
+        assert_eq!(authority.emission_due(7_200).unwrap(), (3_600 + 1_800, 7_200));
+
+        // Splitting an epoch across cranks loses no dust
+        let mut authority_dust = emission_schedule(1_000, BPS_DENOMINATOR as u16, 1);
+        let (first, settled) = authority_dust.emission_due(1_001).unwrap();
+        authority_dust.last_mint_timestamp = settled;
+        let (second, _) = authority_dust.emission_due(3_600).unwrap();
+        assert_eq!(first + second, 1_000);
+
+        authority.last_mint_timestamp = 7_200;
+        assert_eq!(authority.emission_due(7_200).unwrap(), (0, 7_200));
+    }
+
+    #[test]
+    fn emission_due_settles_at_most_max_crank_epochs() {
+        let authority = emission_schedule(100, BPS_DENOMINATOR as u16, 1);
+        let (due, settled) = authority.emission_due(3_600 * 100).unwrap();
+        assert_eq!(due, 100 * MAX_CRANK_EPOCHS as u64);
+        assert_eq!(settled, 3_600 * MAX_CRANK_EPOCHS as i64);
+    }
+
+    #[test]
+    fn emission_epoch_length_change_keeps_epoch_numbering() {
+        let mut authority = emission_schedule(100, 9_000, 1);
+        authority.last_mint_timestamp = 3_600 * 5 + 100;
+        let change = ParameterChange::EmissionSchedule {
+            epoch_length: 1_800,
+            initial_per_epoch: 100,
+            decay_bps: 9_000,
+            decay_interval: 1,
+        };
+        authority.apply_parameter_change(&change, 3_600 * 6).unwrap();
+        assert_eq!(authority.emission_epoch_length, 1_800);
+        assert_eq!(authority.emission_epoch_at(authority.last_mint_timestamp), 5);
+    }
+
+    // Vesting
+
+    fn vesting_escrow(cliff_seconds: i64, duration_seconds: i64) -> VestingEscrow {
+        VestingEscrow {
+            cliff_seconds,
+            duration_seconds,
+            tranche_interval: VestingEscrow::tranche_interval_for(cliff_seconds, duration_seconds).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn vesting_deposits_keep_their_own_schedule() {
+        let mut vesting = vesting_escrow(100, 100);
+        vesting.deposit(1_000, 0).unwrap();
+
+        // Nothing before the cliff, then a linear release
+        vesting.accrue(100);
+        assert_eq!(vesting.vested_unreleased, 0);
+        vesting.accrue(150);
+        let vested_at_150 = vesting.vested_unreleased;
+        assert!(vested_at_150 > 0 && vested_at_150 < 1_000);
+
+        // A later deposit neither stalls the first one nor vests before its own cliff
+        vesting.deposit(500, 150).unwrap();
+        assert_eq!(vesting.vested_unreleased, vested_at_150);
+        vesting.accrue(250);
+        assert_eq!(vesting.vested_unreleased, 1_000);
+        assert_eq!(vesting.unvested, 500);
+        assert_eq!(vesting.tranches.len(), 1);
+
+        vesting.accrue(10_000);
+        assert_eq!(vesting.vested_unreleased, 1_500);
+        assert_eq!(vesting.unvested, 0);
+        assert!(vesting.tranches.is_empty());
+    }
+
+    #[test]
+    fn vesting_merges_deposits_within_a_tranche_interval() {
+        let mut vesting = vesting_escrow(0, 3_100);
+        assert_eq!(vesting.tranche_interval, 100);
+        vesting.deposit(10, 1).unwrap();
+        vesting.deposit(10, 99).unwrap();
+        assert_eq!(vesting.tranches.len(), 1);
+        vesting.deposit(10, 100).unwrap();
+        assert_eq!(vesting.tranches.len(), 2);
+        assert_eq!(vesting.total_deposited, 30);
+    }
+
+    #[test]
+    fn vesting_tranche_ring_never_fills_up() {
+        let mut vesting = vesting_escrow(500, 1_000);
+        for time in (0..20_000).step_by(7) {
+            vesting.deposit(3, time).unwrap();
+            assert!(vesting.tranches.len() <= MAX_VESTING_TRANCHES);
+        }
+        vesting.accrue(i64::MAX / 2);
+        assert_eq!(vesting.vested_unreleased, vesting.total_deposited);
+    }
+
+    // Rate limits
+
+    #[test]
+    fn apply_rate_limits_enforces_each_window() {
+        let limits = MintingAuthority {
+            max_mints_per_player_per_minute: 2,
+            max_tokens_per_player_per_hour: 100,
+            max_tokens_per_player_per_day: 150,
+            ..Default::default()
+        };
+        let mut stats = PlayerMintStats::default();
+
+        stats.apply_rate_limits(&limits, 10, 0).unwrap();
+        stats.apply_rate_limits(&limits, 10, 30).unwrap();
+        assert_error(
+            stats.apply_rate_limits(&limits, 10, 59),
+            GameTokenError::PlayerRateLimitExceeded,
+        );
+
+        stats.apply_rate_limits(&limits, 80, 60).unwrap();
+        assert_error(
+            stats.apply_rate_limits(&limits, 1, 120),
+            GameTokenError::PlayerHourlyCapExceeded,
+        );
+
+        stats.apply_rate_limits(&limits, 50, 3_600).unwrap();
+        assert_error(
+            stats.apply_rate_limits(&limits, 1, 3_660),
+            GameTokenError::PlayerDailyCapExceeded,
+        );
+
+        stats.apply_rate_limits(&limits, 100, 86_400).unwrap();
+        assert_eq!(stats.tokens_this_day, 100);
+    }
+
+    #[test]
+    fn apply_rate_limits_of_zero_are_unlimited() {
+        let limits = MintingAuthority::default();
+        let mut stats = PlayerMintStats::default();
+        for _ in 0..10 {
+            stats.apply_rate_limits(&limits, u64::MAX / 20, 0).unwrap();
+        }
+    }
+
+    // Sessions
+
+    #[test]
+    fn session_daily_cap_spans_restarted_sessions() {
+        let mut session = PlayerSession {
+            earn_cap: 60,
+            daily_cap: 100,
+            ..Default::default()
+        };
+        let restart = |session: &mut PlayerSession| {
+            session.is_open = true;
+            session.tokens_earned = 0;
+        };
+
+        restart(&mut session);
+        session.record_earn(60, 10).unwrap();
+        assert_error(session.record_earn(1, 20), GameTokenError::SessionCapExceeded);
+
+        // A fresh session gets a fresh per-session cap but not a fresh daily cap
+        restart(&mut session);
+        session.record_earn(40, 30).unwrap();
+        assert_error(session.record_earn(1, 40), GameTokenError::SessionDailyCapExceeded);
+
+        restart(&mut session);
+        session.record_earn(60, SECONDS_PER_DAY).unwrap();
+        assert_eq!(session.tokens_today, 60);
+    }
+
+    // Staking
+
+    #[test]
+    fn consecutive_unstakes_keep_their_own_cooldown() {
+        let mut stake = PlayerStake::default();
+        stake.request_unstake(100, 1_000).unwrap();
+        stake.request_unstake(50, 1_500).unwrap();
+        assert_eq!(stake.unstaking_amount, 150);
+
+        // The second request does not push back the first
+        assert_eq!(stake.withdrawable(999), 0);
+        assert_eq!(stake.withdrawable(1_000), 100);
+        stake.release_withdrawable(1_000);
+        assert_eq!(stake.unstaking_amount, 50);
+        assert_eq!(stake.withdrawable(1_499), 0);
+
+        stake.release_withdrawable(1_500);
+        assert_eq!(stake.unstaking_amount, 0);
+        assert!(stake.unstake_requests.is_empty());
+    }
+
+    #[test]
+    fn unstake_requests_are_bounded() {
+        let mut stake = PlayerStake::default();
+        for i in 0..MAX_UNSTAKE_REQUESTS as i64 {
+            stake.request_unstake(1, i).unwrap();
+        }
+        // Same cooldown end merges into the last request
+        stake.request_unstake(1, MAX_UNSTAKE_REQUESTS as i64 - 1).unwrap();
+        assert_error(
+            stake.request_unstake(1, MAX_UNSTAKE_REQUESTS as i64),
+            GameTokenError::TooManyUnstakeRequests,
+        );
+    }
+
+    // Voucher signatures
+
+    const SIGNATURE_OFFSET: u16 = 16 + 32;
+    const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+
+    fn ed25519_instruction_data(signer: &Pubkey, message: &[u8], data_ix_index: u16) -> Vec<u8> {
+        let mut data = vec![1, 0];
+        for value in [
+            SIGNATURE_OFFSET,
+            data_ix_index,
+            16,
+            data_ix_index,
+            MESSAGE_OFFSET,
+            message.len() as u16,
+            data_ix_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[7; 64]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    // Runs `verify_ed25519_instruction` as the second instruction of a transaction
+    // whose first instruction is (`program_id`, `data`)
+    fn verify_after(program_id: &Pubkey, data: &[u8], signer: &Pubkey, message: &[u8]) -> Result<()> {
+        let game_token_account = key(42);
+        let instructions = [
+            BorrowedInstruction {
+                program_id,
+                accounts: vec![],
+                data,
+            },
+            BorrowedInstruction {
+                program_id: &crate::ID,
+                accounts: vec![BorrowedAccountMeta {
+                    pubkey: &game_token_account,
+                    is_signer: false,
+                    is_writable: true,
+                }],
+                data: &[],
+            },
+        ];
+        let mut sysvar_data = instructions_sysvar::construct_instructions_data(&instructions);
+        instructions_sysvar::store_current_index_checked(&mut sysvar_data, 1).unwrap();
+
+        let mut lamports = 0;
+        let owner = Pubkey::default();
+        let sysvar_info = AccountInfo::new(
+            &INSTRUCTIONS_SYSVAR_ID,
+            false,
+            false,
+            &mut lamports,
+            &mut sysvar_data,
+            &owner,
+            false,
+            0,
+        );
+        verify_ed25519_instruction(&sysvar_info, signer, message)
+    }
+
+    #[test]
+    fn verify_ed25519_instruction_accepts_matching_voucher() {
+        let server = key(1);
+        let message = earn_voucher_message(&key(2), 100, 1, 1_000);
+        let data = ed25519_instruction_data(&server, &message, u16::MAX);
+        verify_after(&ed25519_program::ID, &data, &server, &message).unwrap();
+    }
+
+    #[test]
+    fn verify_ed25519_instruction_rejects_mismatches() {
+        let server = key(1);
+        let message = earn_voucher_message(&key(2), 100, 1, 1_000);
+        let data = ed25519_instruction_data(&server, &message, u16::MAX);
+
+        // Signed by someone else
+        assert_error(
+            verify_after(&ed25519_program::ID, &data, &key(3), &message),
+            GameTokenError::InvalidVoucherSignature,
+        );
+        // Different voucher
+        let other = earn_voucher_message(&key(2), 101, 1, 1_000);
+        assert_error(
+            verify_after(&ed25519_program::ID, &data, &server, &other),
+            GameTokenError::InvalidVoucherSignature,
+        );
+        // Not an ed25519 instruction
+        assert_error(
+            verify_after(&key(9), &data, &server, &message),
+            GameTokenError::InvalidVoucherSignature,
+        );
+        // Signature data borrowed from another instruction
+        let borrowed = ed25519_instruction_data(&server, &message, 1);
+        assert_error(
+            verify_after(&ed25519_program::ID, &borrowed, &server, &message),
+            GameTokenError::InvalidVoucherSignature,
+        );
+    }
+
+    // Leaderboard
+
+    #[test]
+    fn leaderboard_record_keeps_top_entries_sorted() {
+        let mut leaderboard = Leaderboard::default();
+        for i in 1..=LEADERBOARD_SIZE as u8 {
+            leaderboard.record(key(i), i as u64 * 10);
+        }
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+        assert_eq!(leaderboard.entries[0].player, key(LEADERBOARD_SIZE as u8));
+
+        // Not enough to enter a full board
+        leaderboard.record(key(100), 10);
+        assert!(leaderboard.entries.iter().all(|entry| entry.player != key(100)));
+
+        // Beats the last entry and takes its place
+        leaderboard.record(key(101), 55);
+        assert!(leaderboard.entries.iter().all(|entry| entry.player != key(1)));
+        assert_eq!(leaderboard.entries[5].player, key(101));
+
+        // Existing entries are updated in place and re-sorted
+        leaderboard.record(key(2), 1_000);
+        assert_eq!(leaderboard.entries[0].player, key(2));
+        assert_eq!(leaderboard.entries[0].tokens_earned, 1_000);
+        assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+        assert!(leaderboard
+            .entries
+            .windows(2)
+            .all(|pair| pair[0].tokens_earned >= pair[1].tokens_earned));
+
+        // Ties keep the earlier arrival ahead
+        leaderboard.record(key(102), 1_000);
+        assert_eq!(leaderboard.entries[1].player, key(102));
+        assert_eq!(leaderboard.entries[0].player, key(2));
+    }
+
+    // Daily check-in
+
+    #[test]
+    fn check_in_builds_and_resets_streaks() {
+        let mut stats = PlayerMintStats::default();
+        assert_eq!(stats.record_check_in(20_000).unwrap(), 0);
+        assert_eq!(stats.current_streak, 1);
+        assert_error(stats.record_check_in(20_000), GameTokenError::AlreadyCheckedIn);
+
+        stats.record_check_in(20_001).unwrap();
+        stats.record_check_in(20_002).unwrap();
+        assert_eq!(stats.current_streak, 3);
+
+        // A missed day without freezes restarts the streak
+        stats.record_check_in(20_004).unwrap();
+        assert_eq!(stats.current_streak, 1);
+        assert_eq!(stats.longest_streak, 3);
+        assert_eq!(stats.total_check_ins, 4);
+    }
+
+    #[test]
+    fn check_in_spends_freezes_to_bridge_gaps() {
+        let mut stats = PlayerMintStats {
+            streak_freezes: 2,
+            ..Default::default()
+        };
+        stats.record_check_in(100).unwrap();
+        stats.record_check_in(101).unwrap();
+
+        // Two missed days, covered by two freezes
+        assert_eq!(stats.record_check_in(104).unwrap(), 2);
+        assert_eq!(stats.current_streak, 3);
+        assert_eq!(stats.streak_freezes, 0);
+
+        // A gap larger than the freezes held resets and keeps them
+        stats.streak_freezes = 1;
+        assert_eq!(stats.record_check_in(107).unwrap(), 0);
+        assert_eq!(stats.current_streak, 1);
+        assert_eq!(stats.streak_freezes, 1);
+        assert_eq!(stats.longest_streak, 3);
+    }
+
+    #[test]
+    fn streak_reward_uses_last_entry_past_table_end() {
+        let config = StreakConfig {
+            reward_table: vec![10, 20, 30],
+            ..Default::default()
+        };
+        assert_eq!(config.reward_for(1), 10);
+        assert_eq!(config.reward_for(3), 30);
+        assert_eq!(config.reward_for(365), 30);
+        assert_eq!(StreakConfig::default().reward_for(1), 0);
+    }
+
+    // Multisig
+
+    fn multisig(threshold: u8) -> Multisig {
+        Multisig {
+            owners: vec![key(1), key(2), key(3)],
+            threshold,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn multisig_owner_validation() {
+        let owners = vec![key(1), key(2), key(3)];
+        assert!(validate_multisig_owners(&owners, 2).is_ok());
+        assert_error(validate_multisig_owners(&owners, 0), GameTokenError::InvalidThreshold);
+        assert_error(validate_multisig_owners(&owners, 4), GameTokenError::InvalidThreshold);
+        assert_error(
+            validate_multisig_owners(&[key(1), key(1)], 1),
+            GameTokenError::InvalidMultisigOwners,
+        );
+        assert_error(validate_multisig_owners(&[], 1), GameTokenError::InvalidMultisigOwners);
+    }
+
+    #[test]
+    fn multisig_propose_approve_execute_flow() {
+        let multisig = multisig(2);
+        assert_error(multisig.proposal_signers(&key(9)), GameTokenError::NotMultisigOwner);
+
+        let mut transaction = MultisigTransaction {
+            signers: multisig.proposal_signers(&key(1)).unwrap(),
+            owner_set_seqno: multisig.owner_set_seqno,
+            ..Default::default()
+        };
+        assert_eq!(transaction.approvals(), 1);
+        assert_error(transaction.check_executable(&multisig), GameTokenError::NotEnoughApprovals);
+
+        assert_error(transaction.approve(&multisig, &key(9)), GameTokenError::NotMultisigOwner);
+        // Approving twice counts once
+        assert_eq!(transaction.approve(&multisig, &key(1)).unwrap(), 1);
+        assert_eq!(transaction.approve(&multisig, &key(3)).unwrap(), 2);
+        transaction.check_executable(&multisig).unwrap();
+
+        transaction.did_execute = true;
+        assert_error(
+            transaction.check_executable(&multisig),
+            GameTokenError::TransactionAlreadyExecuted,
+        );
+        assert_error(
+            transaction.approve(&multisig, &key(2)),
+            GameTokenError::TransactionAlreadyExecuted,
+        );
+    }
+
+    #[test]
+    fn multisig_owner_change_invalidates_pending_transactions() {
+        let mut multisig = multisig(1);
+        let mut transaction = MultisigTransaction {
+            signers: multisig.proposal_signers(&key(2)).unwrap(),
+            owner_set_seqno: multisig.owner_set_seqno,
+            ..Default::default()
+        };
+        transaction.check_executable(&multisig).unwrap();
+
+        multisig.owner_set_seqno += 1;
+        assert_error(transaction.check_executable(&multisig), GameTokenError::StaleTransaction);
+        assert_error(transaction.approve(&multisig, &key(1)), GameTokenError::StaleTransaction);
+    }
 }
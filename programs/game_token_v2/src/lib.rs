@@ -3,11 +3,14 @@ use solana_program::{
     declare_id,
     entrypoint,
     entrypoint::ProgramResult,
-    program::invoke_signed,
+    program::{invoke, invoke_signed},
     pubkey::Pubkey,
     msg,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 
@@ -27,17 +30,21 @@ pub fn process_instruction(
             msg!("Instruction: Initialize");
             process_initialize(program_id, accounts)
         }
-        GameTokenInstruction::AutoMint { amount } => {
+        GameTokenInstruction::AutoMint { amount, ui_amount } => {
             msg!("Instruction: AutoMint {}", amount);
-            process_auto_mint(program_id, accounts, amount)
+            process_auto_mint(program_id, accounts, amount, ui_amount)
         }
-        GameTokenInstruction::DistributeGameTokens { amount } => {
+        GameTokenInstruction::DistributeGameTokens { amount, ui_amount } => {
             msg!("Instruction: DistributeGameTokens {}", amount);
-            process_distribute_game_tokens(program_id, accounts, amount)
+            process_distribute_game_tokens(program_id, accounts, amount, ui_amount)
         }
-        GameTokenInstruction::PlayerClaimTokens { amount } => {
+        GameTokenInstruction::PlayerClaimTokens { amount, ui_amount } => {
             msg!("Instruction: PlayerClaimTokens {}", amount);
-            process_player_claim_tokens(program_id, accounts, amount)
+            process_player_claim_tokens(program_id, accounts, amount, ui_amount)
+        }
+        GameTokenInstruction::SetSplitConfig { recipients, dust_recipient } => {
+            msg!("Instruction: SetSplitConfig");
+            process_set_split_config(program_id, accounts, recipients, dust_recipient)
         }
     }
 }
@@ -46,9 +53,10 @@ pub fn process_instruction(
 #[derive(Clone, Debug, PartialEq)]
 pub enum GameTokenInstruction {
     Initialize,
-    AutoMint { amount: u64 },
-    DistributeGameTokens { amount: u64 },
-    PlayerClaimTokens { amount: u64 },
+    AutoMint { amount: u64, ui_amount: bool },
+    DistributeGameTokens { amount: u64, ui_amount: bool },
+    PlayerClaimTokens { amount: u64, ui_amount: bool },
+    SetSplitConfig { recipients: Vec<SplitRecipient>, dust_recipient: u8 },
 }
 
 impl GameTokenInstruction {
@@ -58,29 +66,125 @@ impl GameTokenInstruction {
         match tag {
             0 => Ok(GameTokenInstruction::Initialize),
             1 => {
-                let amount = rest.get(..8)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(ProgramError::InvalidInstructionData)?;
-                Ok(GameTokenInstruction::AutoMint { amount })
+                let (amount, ui_amount) = Self::unpack_amount(rest)?;
+                Ok(GameTokenInstruction::AutoMint { amount, ui_amount })
             }
             2 => {
-                let amount = rest.get(..8)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(ProgramError::InvalidInstructionData)?;
-                Ok(GameTokenInstruction::DistributeGameTokens { amount })
+                let (amount, ui_amount) = Self::unpack_amount(rest)?;
+                Ok(GameTokenInstruction::DistributeGameTokens { amount, ui_amount })
             }
             3 => {
-                let amount = rest.get(..8)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(ProgramError::InvalidInstructionData)?;
-                Ok(GameTokenInstruction::PlayerClaimTokens { amount })
+                let (amount, ui_amount) = Self::unpack_amount(rest)?;
+                Ok(GameTokenInstruction::PlayerClaimTokens { amount, ui_amount })
+            }
+            4 => {
+                let SetSplitConfigInstruction { recipients, dust_recipient } =
+                    SetSplitConfigInstruction::try_from_slice(rest)
+                        .map_err(|_| ProgramError::InvalidInstructionData)?;
+                Ok(GameTokenInstruction::SetSplitConfig { recipients, dust_recipient })
             }
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
+
+    // amount (u64 LE) followed by an optional UI-amount flag byte; a missing flag
+    // means the amount is already in base units
+    fn unpack_amount(rest: &[u8]) -> Result<(u64, bool), ProgramError> {
+        let amount = rest.get(..8)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u64::from_le_bytes)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let ui_amount = rest.get(8).is_some_and(|flag| *flag != 0);
+        Ok((amount, ui_amount))
+    }
+}
+
+// SPL Token / Token-2022 mint layout: mint_authority (36) + supply (8) + decimals (1)
+const MINT_SUPPLY_OFFSET: usize = 36;
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+// Reads (supply, decimals) straight from the mint account data
+pub fn read_mint(mint_info: &AccountInfo) -> Result<(u64, u8), ProgramError> {
+    let data = mint_info.data.borrow();
+    if data.len() <= MINT_DECIMALS_OFFSET {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let supply = u64::from_le_bytes(
+        data[MINT_SUPPLY_OFFSET..MINT_DECIMALS_OFFSET].try_into().unwrap(),
+    );
+    Ok((supply, data[MINT_DECIMALS_OFFSET]))
+}
+
+// Converts a UI amount (whole tokens) to base units; base-unit amounts pass through
+pub fn to_base_units(amount: u64, ui_amount: bool, decimals: u8) -> Result<u64, ProgramError> {
+    if !ui_amount {
+        return Ok(amount);
+    }
+    10u64
+        .checked_pow(decimals as u32)
+        .and_then(|scale| amount.checked_mul(scale))
+        .ok_or(ProgramError::ArithmeticOverflow)
+}
+
+// TransferChecked (12): source, mint, destination, authority + amount + decimals
+fn transfer_checked_data(amount: u64, decimals: u8) -> Vec<u8> {
+    let mut data = vec![12];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(decimals);
+    data
+}
+
+// MintToChecked (14): mint, destination, authority + amount + decimals
+fn mint_to_checked_data(amount: u64, decimals: u8) -> Vec<u8> {
+    let mut data = vec![14];
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(decimals);
+    data
+}
+
+// Creates a program-owned PDA account of `space` bytes. Works even if someone has
+// already sent lamports to the address, where a plain create_account would fail
+fn create_pda_account<'a>(
+    program_id: &Pubkey,
+    payer_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let required_lamports = Rent::get()?.minimum_balance(space);
+
+    if account_info.lamports() == 0 {
+        return invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                account_info.key,
+                required_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer_info.clone(), account_info.clone(), system_program_info.clone()],
+            &[seeds],
+        );
+    }
+
+    let top_up = required_lamports.saturating_sub(account_info.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer_info.key, account_info.key, top_up),
+            &[payer_info.clone(), account_info.clone(), system_program_info.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(account_info.key, space as u64),
+        &[account_info.clone(), system_program_info.clone()],
+        &[seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(account_info.key, program_id),
+        &[account_info.clone(), system_program_info.clone()],
+        &[seeds],
+    )
 }
 
 // Account structures
@@ -200,6 +304,126 @@ impl Pack for GameTokenPools {
     }
 }
 
+// Mint split: up to MAX_SPLIT_RECIPIENTS recipients with basis-point weights
+// summing to BPS_DENOMINATOR; rounding dust goes to the dust recipient
+pub const MAX_SPLIT_RECIPIENTS: usize = 8;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+const SPLIT_RECIPIENT_LEN: usize = 32 + 2; // recipient + weight_bps
+
+#[derive(BorshDeserialize, BorshSerialize, Clone, Debug, Default, PartialEq)]
+pub struct SplitRecipient {
+    pub recipient: Pubkey,
+    pub weight_bps: u16,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SplitConfig {
+    pub recipients: Vec<SplitRecipient>,
+    pub dust_recipient: u8,
+}
+
+impl SplitConfig {
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        let recipients = &self.recipients;
+        if recipients.is_empty()
+            || recipients.len() > MAX_SPLIT_RECIPIENTS
+            || self.dust_recipient as usize >= recipients.len()
+        {
+            return Err(ProgramError::InvalidArgument);
+        }
+
+        let mut total_bps: u64 = 0;
+        for (i, recipient) in recipients.iter().enumerate() {
+            if recipient.weight_bps == 0
+                || recipients[..i].iter().any(|other| other.recipient == recipient.recipient)
+            {
+                return Err(ProgramError::InvalidArgument);
+            }
+            total_bps += recipient.weight_bps as u64;
+        }
+        if total_bps != BPS_DENOMINATOR {
+            return Err(ProgramError::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    // Share per recipient, in config order; they always sum to `amount`
+    pub fn compute_shares(&self, amount: u64) -> Result<Vec<u64>, ProgramError> {
+        let mut shares: Vec<u64> = self
+            .recipients
+            .iter()
+            .map(|recipient| (amount as u128 * recipient.weight_bps as u128 / BPS_DENOMINATOR as u128) as u64)
+            .collect();
+
+        let dust = amount - shares.iter().sum::<u64>();
+        let dust_share = shares
+            .get_mut(self.dust_recipient as usize)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        *dust_share += dust;
+        Ok(shares)
+    }
+
+    // Total share going to `recipient`
+    pub fn share_of(&self, shares: &[u64], recipient: &Pubkey) -> u64 {
+        self.recipients
+            .iter()
+            .zip(shares.iter())
+            .filter(|(entry, _)| entry.recipient == *recipient)
+            .map(|(_, share)| *share)
+            .sum()
+    }
+}
+
+impl Sealed for SplitConfig {}
+impl IsInitialized for SplitConfig {
+    fn is_initialized(&self) -> bool {
+        !self.recipients.is_empty()
+    }
+}
+
+impl Pack for SplitConfig {
+    // count + recipients + dust_recipient
+    const LEN: usize = 1 + MAX_SPLIT_RECIPIENTS * SPLIT_RECIPIENT_LEN + 1;
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < Self::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let count = src[0] as usize;
+        if count > MAX_SPLIT_RECIPIENTS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let recipients = src[1..]
+            .chunks_exact(SPLIT_RECIPIENT_LEN)
+            .take(count)
+            .map(|entry| SplitRecipient {
+                recipient: Pubkey::new_from_array(entry[..32].try_into().unwrap()),
+                weight_bps: u16::from_le_bytes(entry[32..].try_into().unwrap()),
+            })
+            .collect();
+        let dust_recipient = src[Self::LEN - 1];
+
+        Ok(SplitConfig {
+            recipients,
+            dust_recipient,
+        })
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        dst[..Self::LEN].fill(0);
+        dst[0] = self.recipients.len() as u8;
+        for (entry, recipient) in dst[1..]
+            .chunks_exact_mut(SPLIT_RECIPIENT_LEN)
+            .zip(self.recipients.iter())
+        {
+            entry[..32].copy_from_slice(recipient.recipient.as_ref());
+            entry[32..].copy_from_slice(&recipient.weight_bps.to_le_bytes());
+        }
+        dst[Self::LEN - 1] = self.dust_recipient;
+    }
+}
+
 // Instruction processors
 pub fn process_initialize(
     program_id: &Pubkey,
@@ -240,22 +464,26 @@ pub fn process_auto_mint(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
+    ui_amount: bool,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
     let authority_info = next_account_info(account_info_iter)?;
     let game_pools_info = next_account_info(account_info_iter)?;
     let _owner_token_info = next_account_info(account_info_iter)?;
-    let _game_token_mint_info = next_account_info(account_info_iter)?;
-    let _token_program_info = next_account_info(account_info_iter)?;
+    let game_token_mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
     let owner_info = next_account_info(account_info_iter)?;
+    let split_config_info = next_account_info(account_info_iter)?;
+    // Followed by one token account per split recipient, in config order
+    let recipient_infos = account_info_iter.as_slice();
 
     if !owner_info.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
     // Verify PDAs
-    let (authority_pda, _) = Pubkey::find_program_address(&[b"minting_authority"], program_id);
+    let (authority_pda, authority_bump) = Pubkey::find_program_address(&[b"minting_authority"], program_id);
     if authority_pda != *authority_info.key {
         return Err(ProgramError::InvalidAccountData);
     }
@@ -265,32 +493,163 @@ pub fn process_auto_mint(
         return Err(ProgramError::InvalidAccountData);
     }
 
+    let (split_config_pda, _) = Pubkey::find_program_address(&[b"split_config_v2"], program_id);
+    if split_config_pda != *split_config_info.key || split_config_info.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let split_config = SplitConfig::unpack(&split_config_info.data.borrow())?;
+    if recipient_infos.len() != split_config.recipients.len() {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
     // Load and update authority
     let mut authority = MintingAuthority::unpack_from_slice(&authority_info.data.borrow())?;
     if authority.owner != *owner_info.key {
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Check supply limits
-    if !authority.is_infinite && authority.total_minted + amount > authority.max_supply {
+    let mut game_pools = GameTokenPools::unpack_from_slice(&game_pools_info.data.borrow())?;
+    if game_pools.game_token_mint != *game_token_mint_info.key
+        || game_token_mint_info.owner != token_program_info.key
+    {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (mint_supply, decimals) = read_mint(game_token_mint_info)?;
+    let amount = to_base_units(amount, ui_amount, decimals)?;
+
+    // Check supply limits against both the tracked total and the mint's real supply
+    let new_total_minted = authority
+        .total_minted
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let new_supply = mint_supply
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if !authority.is_infinite && new_total_minted.max(new_supply) > authority.max_supply {
         msg!("Supply limit exceeded");
         return Err(ProgramError::InvalidArgument);
     }
 
-    // Calculate 80/20 distribution
-    let game_amount = amount * 80 / 100;
-    let owner_amount = amount * 20 / 100;
+    // Calculate distribution from the split config; the game pool's share is the one
+    // addressed to the game pool token account
+    let shares = split_config.compute_shares(amount)?;
+    let (game_pools_token_pda, _) = Pubkey::find_program_address(&[b"game_pools_v2_token_account"], program_id);
+    let game_amount = split_config.share_of(&shares, &game_pools_token_pda);
+
+    // Mint each share to its recipient, signed by the minting authority PDA
+    for ((recipient, &share), recipient_info) in split_config
+        .recipients
+        .iter()
+        .zip(shares.iter())
+        .zip(recipient_infos.iter())
+    {
+        if recipient.recipient != *recipient_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if share == 0 {
+            continue;
+        }
+
+        let mint_ix = solana_program::instruction::Instruction {
+            program_id: *token_program_info.key,
+            accounts: vec![
+                solana_program::instruction::AccountMeta::new(*game_token_mint_info.key, false),
+                solana_program::instruction::AccountMeta::new(*recipient_info.key, false),
+                solana_program::instruction::AccountMeta::new_readonly(authority_pda, true),
+            ],
+            data: mint_to_checked_data(share, decimals),
+        };
+        invoke_signed(
+            &mint_ix,
+            &[
+                game_token_mint_info.clone(),
+                recipient_info.clone(),
+                authority_info.clone(), // PDA as signer
+            ],
+            &[&[b"minting_authority", &[authority_bump]]],
+        )?;
+        msg!("Split share {} -> {}", share, recipient.recipient);
+    }
 
     // Update tracking
-    authority.total_minted += amount;
+    authority.total_minted = new_total_minted;
     authority.pack_into_slice(&mut authority_info.data.borrow_mut());
 
-    // Load and update game pools
-    let mut game_pools = GameTokenPools::unpack_from_slice(&game_pools_info.data.borrow())?;
-    game_pools.active_pool += game_amount;
+    // Update game pools
+    game_pools.active_pool = game_pools
+        .active_pool
+        .checked_add(game_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
     game_pools.pack_into_slice(&mut game_pools_info.data.borrow_mut());
 
-    msg!("Auto-minted {} tokens: {} game + {} owner (optimized)", amount, game_amount, owner_amount);
+    msg!("Auto-minted {} tokens: {} to game pool", amount, game_amount);
+    Ok(())
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct SetSplitConfigInstruction {
+    pub recipients: Vec<SplitRecipient>,
+    pub dust_recipient: u8,
+}
+
+// Create or replace the auto-mint split (minting authority owner only); the
+// split config PDA is created on first use, paid for by the payer
+pub fn process_set_split_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    recipients: Vec<SplitRecipient>,
+    dust_recipient: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let authority_info = next_account_info(account_info_iter)?;
+    let split_config_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let payer_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if !owner_info.is_signer || !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify PDAs
+    let (authority_pda, _) = Pubkey::find_program_address(&[b"minting_authority"], program_id);
+    if authority_pda != *authority_info.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let (split_config_pda, split_config_bump) = Pubkey::find_program_address(&[b"split_config_v2"], program_id);
+    if split_config_pda != *split_config_info.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let authority = MintingAuthority::unpack_from_slice(&authority_info.data.borrow())?;
+    if authority.owner != *owner_info.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let split_config = SplitConfig {
+        recipients,
+        dust_recipient,
+    };
+    split_config.validate()?;
+
+    if split_config_info.owner != program_id {
+        create_pda_account(
+            program_id,
+            payer_info,
+            split_config_info,
+            system_program_info,
+            SplitConfig::LEN,
+            &[b"split_config_v2", &[split_config_bump]],
+        )?;
+    }
+    split_config.pack_into_slice(&mut split_config_info.data.borrow_mut());
+
+    for recipient in split_config.recipients.iter() {
+        msg!("Split recipient {}: {} bps", recipient.recipient, recipient.weight_bps);
+    }
+    msg!("Split config updated: {} recipients, dust to #{}", split_config.recipients.len(), dust_recipient);
     Ok(())
 }
 
@@ -303,6 +662,7 @@ pub fn process_distribute_game_tokens(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
+    ui_amount: bool,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
@@ -334,21 +694,22 @@ pub fn process_distribute_game_tokens(
     if game_pools.authority != *owner_info.key {
         return Err(ProgramError::InvalidAccountData);
     }
-
-    // Create transfer instruction data manually
-    // Token transfer instruction: 12 (transfer) + source + dest + authority + amount
-    let mut transfer_data = vec![12]; // transfer instruction
-    transfer_data.extend_from_slice(&amount.to_le_bytes()); // amount
+    if game_pools.game_token_mint != *game_token_mint_info.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (_, decimals) = read_mint(game_token_mint_info)?;
+    let amount = to_base_units(amount, ui_amount, decimals)?;
 
     // Create transfer instruction
     let transfer_ix = solana_program::instruction::Instruction {
         program_id: *token_program_info.key,
         accounts: vec![
             solana_program::instruction::AccountMeta::new(*game_pools_token_account_info.key, false),
+            solana_program::instruction::AccountMeta::new_readonly(*game_token_mint_info.key, false),
             solana_program::instruction::AccountMeta::new(*player_token_account_info.key, false),
-            solana_program::instruction::AccountMeta::new_readonly(game_pools_pda, false),
+            solana_program::instruction::AccountMeta::new_readonly(game_pools_pda, true),
         ],
-        data: transfer_data,
+        data: transfer_checked_data(amount, decimals),
     };
 
     // Invoke token transfer via CPI
@@ -356,6 +717,7 @@ pub fn process_distribute_game_tokens(
         &transfer_ix,
         &[
             game_pools_token_account_info.clone(),
+            game_token_mint_info.clone(),
             player_token_account_info.clone(),
             game_pools_info.clone(), // PDA as signer
         ],
@@ -370,6 +732,7 @@ pub fn process_player_claim_tokens(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
+    ui_amount: bool,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
@@ -397,23 +760,25 @@ pub fn process_player_claim_tokens(
 
     // Load game pools data
     let game_pools = GameTokenPools::unpack_from_slice(&game_pools_info.data.borrow())?;
+    if game_pools.game_token_mint != *game_token_mint_info.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (_, decimals) = read_mint(game_token_mint_info)?;
+    let amount = to_base_units(amount, ui_amount, decimals)?;
 
     // Check if game pool has sufficient balance (this is a simple check)
     // In production, you'd want more sophisticated validation
 
-    // Create transfer instruction data
-    let mut transfer_data = vec![12]; // transfer instruction
-    transfer_data.extend_from_slice(&amount.to_le_bytes());
-
     // Create transfer instruction
     let transfer_ix = solana_program::instruction::Instruction {
         program_id: *token_program_info.key,
         accounts: vec![
             solana_program::instruction::AccountMeta::new(*game_pools_token_account_info.key, false),
+            solana_program::instruction::AccountMeta::new_readonly(*game_token_mint_info.key, false),
             solana_program::instruction::AccountMeta::new(*player_token_account_info.key, false),
-            solana_program::instruction::AccountMeta::new_readonly(game_pools_pda, false),
+            solana_program::instruction::AccountMeta::new_readonly(game_pools_pda, true),
         ],
-        data: transfer_data,
+        data: transfer_checked_data(amount, decimals),
     };
 
     // Invoke token transfer via CPI with PDA signature
@@ -421,6 +786,7 @@ pub fn process_player_claim_tokens(
         &transfer_ix,
         &[
             game_pools_token_account_info.clone(),
+            game_token_mint_info.clone(),
             player_token_account_info.clone(),
             game_pools_info.clone(), // PDA as signer
         ],
@@ -7,6 +7,7 @@ use solana_program::{
     pubkey::Pubkey,
     msg,
     program_error::ProgramError,
+    pubkey,
 };
 
 declare_id!("Transfer11111111111111111111111111111112");
@@ -40,20 +41,38 @@ pub fn process_instruction(
         return Err(ProgramError::InvalidAccountData);
     }
 
-    // Parse amount from instruction data
-    let amount = u64::from_le_bytes(instruction_data[1..9].try_into().unwrap());
+    // Parse amount from instruction data, followed by an optional UI-amount flag
+    let amount = instruction_data
+        .get(1..9)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let ui_amount = instruction_data.get(9).is_some_and(|flag| *flag != 0);
 
-    msg!("Transferring {} tokens from game pool to player", amount / 1_000_000);
+    // Decimals come from the mint, so any SPL / Token-2022 mint works
+    let decimals = read_mint_decimals(mint_account, token_program)?;
+    let amount = if ui_amount {
+        10u64
+            .checked_pow(decimals as u32)
+            .and_then(|scale| amount.checked_mul(scale))
+            .ok_or(ProgramError::ArithmeticOverflow)?
+    } else {
+        amount
+    };
+
+    msg!("Transferring {} tokens from game pool to player", format_ui_amount(amount, decimals)?);
 
-    // Create transfer instruction data
-    let mut transfer_data = vec![12]; // transfer instruction
+    // TransferChecked: amount + decimals
+    let mut transfer_data = vec![12];
     transfer_data.extend_from_slice(&amount.to_le_bytes());
+    transfer_data.push(decimals);
 
     // Create the transfer instruction
     let transfer_ix = solana_program::instruction::Instruction {
         program_id: *token_program.key,
         accounts: vec![
             solana_program::instruction::AccountMeta::new(*source_account.key, false),
+            solana_program::instruction::AccountMeta::new_readonly(*mint_account.key, false),
             solana_program::instruction::AccountMeta::new(*dest_account.key, false),
             solana_program::instruction::AccountMeta::new_readonly(expected_pda, true),
         ],
@@ -65,14 +84,55 @@ pub fn process_instruction(
         &transfer_ix,
         &[
             source_account.clone(),
+            mint_account.clone(),
             dest_account.clone(),
             authority.clone(),
         ],
         &[&[b"transfer_authority", &[bump]]],
     )?;
 
-    msg!("Successfully transferred {} tokens", amount / 1_000_000);
+    msg!("Successfully transferred {} tokens", format_ui_amount(amount, decimals)?);
     Ok(())
 }
 
+// Decimals byte of an SPL Token / Token-2022 mint (after mint_authority and supply)
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+fn read_mint_decimals(
+    mint_account: &AccountInfo,
+    token_program: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    // Only trust the layout of a mint owned by the token program we invoke
+    if *token_program.key != TOKEN_PROGRAM_ID && *token_program.key != TOKEN_2022_PROGRAM_ID {
+        msg!("Unsupported token program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if mint_account.owner != token_program.key {
+        msg!("Mint is not owned by the token program");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    mint_account
+        .data
+        .borrow()
+        .get(MINT_DECIMALS_OFFSET)
+        .copied()
+        .ok_or(ProgramError::InvalidAccountData)
+}
+
+// Formats a base-unit amount as a decimal UI amount for logs
+fn format_ui_amount(amount: u64, decimals: u8) -> Result<String, ProgramError> {
+    if decimals == 0 {
+        return Ok(amount.to_string());
+    }
+    let scale = 10u128
+        .checked_pow(decimals as u32)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let amount = amount as u128;
+    Ok(format!("{}.{:0width$}", amount / scale, amount % scale, width = decimals as usize))
+}
+
 
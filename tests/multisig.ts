@@ -0,0 +1,190 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Keypair, PublicKey, SystemProgram, TransactionInstruction } from "@solana/web3.js";
+import { assert } from "chai";
+import { GameToken } from "../target/types/game_token";
+
+// End-to-end multisig administration on a local validator (`anchor test`):
+// create a 2-of-3 multisig, then propose, approve and execute admin instructions
+// signed by its signer PDA
+describe("multisig", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.GameToken as Program<GameToken>;
+
+  const owners = [Keypair.generate(), Keypair.generate(), Keypair.generate()];
+  const outsider = Keypair.generate();
+  const createKey = Keypair.generate().publicKey;
+
+  const [multisig] = PublicKey.findProgramAddressSync(
+    [Buffer.from("multisig"), createKey.toBuffer()],
+    program.programId
+  );
+  const [multisigSigner] = PublicKey.findProgramAddressSync(
+    [Buffer.from("multisig_signer"), multisig.toBuffer()],
+    program.programId
+  );
+  const [mintingAuthority] = PublicKey.findProgramAddressSync(
+    [Buffer.from("minting_authority")],
+    program.programId
+  );
+
+  const transactionAddress = (index: number) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("multisig_tx"),
+        multisig.toBuffer(),
+        new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      assert.equal((err as anchor.AnchorError).error?.errorCode?.code, code);
+      return;
+    }
+    assert.fail(`expected ${code}`);
+  };
+
+  // Proposes `ix` from owners[0] and returns the transaction account
+  const propose = async (ix: TransactionInstruction) => {
+    const { transactionCount } = await program.account.multisig.fetch(multisig);
+    const transaction = transactionAddress(transactionCount.toNumber());
+    await program.methods
+      .proposeTransaction(
+        ix.programId,
+        ix.keys.map((key) => ({
+          pubkey: key.pubkey,
+          // The signer PDA signs through execute_transaction
+          isSigner: key.isSigner && !key.pubkey.equals(multisigSigner),
+          isWritable: key.isWritable,
+        })),
+        ix.data
+      )
+      .accountsPartial({ multisig, transaction, proposer: owners[0].publicKey })
+      .signers([owners[0]])
+      .rpc();
+    return transaction;
+  };
+
+  const approve = (transaction: PublicKey, owner: Keypair) =>
+    program.methods
+      .approveTransaction()
+      .accountsPartial({ multisig, transaction, owner: owner.publicKey })
+      .signers([owner])
+      .rpc();
+
+  const execute = (transaction: PublicKey, ix: TransactionInstruction) =>
+    program.methods
+      .executeTransaction()
+      .accountsPartial({ multisig, multisigSigner, transaction, executor: provider.wallet.publicKey })
+      .remainingAccounts([
+        ...ix.keys.map((key) => ({
+          pubkey: key.pubkey,
+          isSigner: false,
+          isWritable: key.isWritable,
+        })),
+        { pubkey: ix.programId, isSigner: false, isWritable: false },
+      ])
+      .rpc();
+
+  before(async () => {
+    for (const payer of [owners[0], outsider]) {
+      const signature = await provider.connection.requestAirdrop(
+        payer.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await provider.connection.confirmTransaction(signature, "confirmed");
+    }
+
+    await program.methods
+      .createMultisig(createKey, owners.map((owner) => owner.publicKey), 2)
+      .accountsPartial({
+        multisig,
+        multisigSigner,
+        payer: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .rpc();
+  });
+
+  it("creates a 2-of-3 multisig", async () => {
+    const account = await program.account.multisig.fetch(multisig);
+    assert.equal(account.threshold, 2);
+    assert.deepEqual(
+      account.owners.map((owner) => owner.toBase58()),
+      owners.map((owner) => owner.publicKey.toBase58())
+    );
+  });
+
+  it("takes ownership of MintingAuthority and pauses minting once approved", async () => {
+    await program.methods
+      .initializeMintingAuthority(0, true, new anchor.BN(0))
+      .accountsPartial({ authority: mintingAuthority, owner: provider.wallet.publicKey })
+      .rpc();
+    await program.methods
+      .proposeOwner(multisigSigner)
+      .accountsPartial({ authority: mintingAuthority, owner: provider.wallet.publicKey })
+      .rpc();
+
+    // accept_owner must be signed by the new owner, i.e. the multisig signer PDA
+    const acceptIx = await program.methods
+      .acceptOwner()
+      .accountsPartial({ authority: mintingAuthority, newOwner: multisigSigner })
+      .instruction();
+    const accept = await propose(acceptIx);
+    await approve(accept, owners[1]);
+    await execute(accept, acceptIx);
+    assert.isTrue(
+      (await program.account.mintingAuthority.fetch(mintingAuthority)).owner.equals(multisigSigner)
+    );
+
+    // A single owner's approval is not enough to pause
+    const pauseIx = await program.methods
+      .emergencyPause(1, 7)
+      .accountsPartial({ authority: mintingAuthority, owner: multisigSigner })
+      .instruction();
+    const pause = await propose(pauseIx);
+    await expectError(execute(pause, pauseIx), "NotEnoughApprovals");
+    await expectError(approve(pause, outsider), "NotMultisigOwner");
+
+    await approve(pause, owners[2]);
+    await execute(pause, pauseIx);
+    const authority = await program.account.mintingAuthority.fetch(mintingAuthority);
+    assert.equal(authority.pauseFlags, 1);
+    assert.equal(authority.pauseReason, 7);
+
+    await expectError(execute(pause, pauseIx), "TransactionAlreadyExecuted");
+  });
+
+  it("rotates owners and invalidates pending proposals", async () => {
+    const unpauseIx = await program.methods
+      .unpause(1)
+      .accountsPartial({ authority: mintingAuthority, owner: multisigSigner })
+      .instruction();
+    const pending = await propose(unpauseIx);
+
+    const newOwners = [owners[0].publicKey, outsider.publicKey];
+    const rotateIx = await program.methods
+      .setMultisigOwners(newOwners, 2)
+      .accountsPartial({ multisig, multisigSigner })
+      .instruction();
+    const rotate = await propose(rotateIx);
+    await approve(rotate, owners[1]);
+    await execute(rotate, rotateIx);
+
+    const account = await program.account.multisig.fetch(multisig);
+    assert.equal(account.ownerSetSeqno, 1);
+    assert.deepEqual(
+      account.owners.map((owner) => owner.toBase58()),
+      newOwners.map((owner) => owner.toBase58())
+    );
+
+    // Proposed under the old owner set, so it can no longer be approved or executed
+    await expectError(approve(pending, outsider), "StaleTransaction");
+    await expectError(execute(pending, unpauseIx), "StaleTransaction");
+  });
+});